@@ -0,0 +1,2 @@
+drop table phonetics;
+alter table word_entries drop column phonetic;
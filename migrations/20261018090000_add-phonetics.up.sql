@@ -0,0 +1,8 @@
+alter table word_entries add column if not exists phonetic text;
+
+create table if not exists phonetics (
+    id serial primary key,
+    word_entry_id int references word_entries (id) on delete cascade,
+    text text,
+    audio text
+);
@@ -0,0 +1,6 @@
+drop table definition_antonyms;
+drop table definition_synonyms;
+alter table word_entries
+    drop column origin,
+    drop column license_name,
+    drop column license_url;
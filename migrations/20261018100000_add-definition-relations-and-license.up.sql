@@ -0,0 +1,16 @@
+alter table word_entries
+    add column if not exists origin text,
+    add column if not exists license_name text,
+    add column if not exists license_url text;
+
+create table if not exists definition_synonyms (
+    id serial primary key,
+    definition_id int references definitions (id) on delete cascade,
+    synonym text not null
+);
+
+create table if not exists definition_antonyms (
+    id serial primary key,
+    definition_id int references definitions (id) on delete cascade,
+    antonym text not null
+);
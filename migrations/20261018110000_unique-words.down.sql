@@ -0,0 +1 @@
+alter table words drop constraint words_word_key;
@@ -0,0 +1,7 @@
+-- Adding a word twice stored it twice, keep the oldest row. The duplicated
+-- entries are removed once they are linked to their words.
+delete from words
+using words as original
+where words.word = original.word and words.id > original.id;
+
+alter table words add constraint words_word_key unique (word);
@@ -0,0 +1 @@
+alter table words drop column fetched_at;
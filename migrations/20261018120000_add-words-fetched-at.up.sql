@@ -0,0 +1 @@
+alter table words add column if not exists fetched_at timestamptz not null default now();
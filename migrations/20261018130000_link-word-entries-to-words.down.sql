@@ -0,0 +1,2 @@
+drop index word_entries_word_id_idx;
+alter table word_entries drop column word_id;
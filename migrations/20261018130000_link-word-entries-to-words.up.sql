@@ -0,0 +1,136 @@
+alter table word_entries add column if not exists word_id int references words (id) on delete cascade;
+
+create temporary table word_entry_fingerprints as
+select
+    word_entries.id,
+    lower(word_entries.word) as word,
+    md5(coalesce(string_agg(
+        meanings.part_of_speech || ':' || definitions.definition,
+        '|' order by meanings.id, definitions.id
+    ), '')) as fingerprint
+from word_entries
+left join meanings on meanings.word_entry_id = word_entries.id
+left join definitions on definitions.meaning_id = meanings.id
+group by word_entries.id;
+
+-- Every add inserted its entries and then its words row in one transaction, so
+-- entries and words follow the same order by id. Walk both side by side and give
+-- each entry the word it was added for, e.g. a "run" entry stored for "running".
+-- The next add starts with an entry when the current word has entries already and:
+--   * the entry repeats one of them, a response never holds the same entry twice,
+--     and the next word is its headword or a word no entry is headed by;
+--   * the entry has another headword and the next word is that headword;
+--   * the entry has another headword, the current word has one of its own entries
+--     and no entry is headed by the next word;
+--   * the entry has another headword, the current word has none of its own entries
+--     and the next word's headword comes before the current one's.
+-- Otherwise the entry stays with the current word, repeats are removed below.
+-- Without timestamps this assumes responses list entries of the added word first.
+do $$
+declare
+    word_ids int[];
+    word_words text[];
+    lemmas boolean[];
+    entry_ids int[];
+    entry_words text[];
+    entry_prints text[];
+    linked int[] := '{}';
+    current int := 1;
+    block text[] := '{}';
+    anchored boolean := false;
+    headword text;
+    print text;
+    next_word text;
+    following text;
+    advance boolean;
+begin
+    select
+        array_agg(words.id order by words.id),
+        array_agg(lower(words.word) order by words.id),
+        array_agg(not exists (
+            select 1 from word_entry_fingerprints where word_entry_fingerprints.word = lower(words.word)
+        ) order by words.id)
+    into word_ids, word_words, lemmas
+    from words;
+
+    select
+        array_agg(id order by id),
+        array_agg(word order by id),
+        array_agg(word || ':' || fingerprint order by id)
+    into entry_ids, entry_words, entry_prints
+    from word_entry_fingerprints;
+
+    if word_ids is null or entry_ids is null then
+        return;
+    end if;
+
+    for i in 1 .. cardinality(entry_ids) loop
+        headword := entry_words[i];
+        print := entry_prints[i];
+        next_word := word_words[current + 1];
+        advance := false;
+
+        if cardinality(block) > 0 and next_word is not null then
+            if print = any(block) then
+                advance := headword = next_word or lemmas[current + 1];
+            elsif headword <> word_words[current] then
+                select entry_words[k] into following
+                from generate_series(i + 1, cardinality(entry_ids)) as k
+                where entry_words[k] in (word_words[current], next_word)
+                order by k
+                limit 1;
+
+                advance := headword = next_word
+                    or (anchored and lemmas[current + 1])
+                    or (not anchored and following = next_word);
+            end if;
+        end if;
+
+        if advance then
+            current := current + 1;
+            block := '{}';
+            anchored := false;
+        end if;
+
+        linked := linked || word_ids[current];
+        block := block || print;
+        anchored := anchored or headword = word_words[current];
+    end loop;
+
+    update word_entries
+    set word_id = links.word_id
+    from unnest(entry_ids, linked) as links (id, word_id)
+    where word_entries.id = links.id;
+end
+$$;
+
+-- Without any words there is nothing to link to, entries become words of their own.
+insert into words (word)
+select distinct word from word_entries where word_id is null
+on conflict (word) do nothing;
+update word_entries set word_id = words.id
+from words
+where word_entries.word_id is null and words.word = word_entries.word;
+
+-- Adding a word twice stored its whole entry tree twice, keep the oldest copy.
+-- Only entries of one word are compared, different words may share a headword.
+delete from word_entries
+where id in (
+    select id
+    from (
+        select
+            word_entries.id,
+            row_number() over (
+                partition by word_entries.word_id, word_entry_fingerprints.word, fingerprint
+                order by word_entries.id
+            ) as position
+        from word_entries
+        join word_entry_fingerprints on word_entry_fingerprints.id = word_entries.id
+    ) as ranked
+    where position > 1
+);
+
+drop table word_entry_fingerprints;
+
+alter table word_entries alter column word_id set not null;
+create index if not exists word_entries_word_id_idx on word_entries (word_id);
@@ -0,0 +1,2 @@
+drop index words_word_lower_key;
+alter table words add constraint words_word_key unique (word);
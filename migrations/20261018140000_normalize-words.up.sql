@@ -0,0 +1,54 @@
+-- Words are now stored trimmed, lowercased and in NFC, keep the oldest of the
+-- words that become equal together with the entries of all of them.
+update word_entries
+set word_id = original.id
+from words, words as original
+where word_entries.word_id = words.id
+    and normalize(lower(btrim(words.word)), NFC) = normalize(lower(btrim(original.word)), NFC)
+    and original.id = (
+        select min(candidates.id)
+        from words as candidates
+        where normalize(lower(btrim(candidates.word)), NFC) = normalize(lower(btrim(words.word)), NFC)
+    )
+    and words.id <> original.id;
+
+-- Each of the merged words may have stored the same entries.
+delete from word_entries
+where id in (
+    select id
+    from (
+        select
+            id,
+            row_number() over (
+                partition by word_id, lower(word), fingerprint
+                order by id
+            ) as position
+        from (
+            select
+                word_entries.id,
+                word_entries.word_id,
+                word_entries.word,
+                md5(coalesce(string_agg(
+                    meanings.part_of_speech || ':' || definitions.definition,
+                    '|' order by meanings.id, definitions.id
+                ), '')) as fingerprint
+            from word_entries
+            left join meanings on meanings.word_entry_id = word_entries.id
+            left join definitions on definitions.meaning_id = meanings.id
+            group by word_entries.id
+        ) as fingerprints
+    ) as ranked
+    where position > 1
+);
+
+delete from words
+using words as original
+where normalize(lower(btrim(words.word)), NFC) = normalize(lower(btrim(original.word)), NFC)
+    and words.id > original.id;
+
+update words
+set word = normalize(lower(btrim(word)), NFC);
+
+alter table words drop constraint words_word_key;
+
+create unique index if not exists words_word_lower_key on words (lower(word));
@@ -0,0 +1,2 @@
+drop index definitions_search_idx;
+alter table definitions drop column search;
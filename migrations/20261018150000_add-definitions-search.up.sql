@@ -0,0 +1,7 @@
+alter table definitions add column if not exists search tsvector
+    generated always as (
+        setweight(to_tsvector('english', definition), 'A')
+            || setweight(to_tsvector('english', coalesce(example, '')), 'B')
+    ) stored;
+
+create index if not exists definitions_search_idx on definitions using gin (search);
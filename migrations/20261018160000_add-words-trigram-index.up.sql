@@ -0,0 +1,3 @@
+create extension if not exists pg_trgm;
+
+create index if not exists words_word_trgm_idx on words using gin (word gin_trgm_ops);
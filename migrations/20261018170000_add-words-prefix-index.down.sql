@@ -0,0 +1 @@
+drop index words_word_prefix_idx;
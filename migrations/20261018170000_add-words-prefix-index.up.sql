@@ -0,0 +1 @@
+create index if not exists words_word_prefix_idx on words (word text_pattern_ops);
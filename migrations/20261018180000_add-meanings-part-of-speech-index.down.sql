@@ -0,0 +1 @@
+drop index meanings_part_of_speech_idx;
@@ -0,0 +1 @@
+create index if not exists meanings_part_of_speech_idx on meanings (part_of_speech, word_entry_id);
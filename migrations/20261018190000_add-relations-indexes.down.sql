@@ -0,0 +1,6 @@
+drop index definition_antonyms_definition_id_idx;
+drop index definition_synonyms_definition_id_idx;
+drop index antonyms_meaning_id_idx;
+drop index synonyms_meaning_id_idx;
+drop index definitions_meaning_id_idx;
+drop index meanings_word_entry_id_idx;
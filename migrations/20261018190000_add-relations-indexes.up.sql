@@ -0,0 +1,6 @@
+create index if not exists meanings_word_entry_id_idx on meanings (word_entry_id);
+create index if not exists definitions_meaning_id_idx on definitions (meaning_id);
+create index if not exists synonyms_meaning_id_idx on synonyms (meaning_id);
+create index if not exists antonyms_meaning_id_idx on antonyms (meaning_id);
+create index if not exists definition_synonyms_definition_id_idx on definition_synonyms (definition_id);
+create index if not exists definition_antonyms_definition_id_idx on definition_antonyms (definition_id);
@@ -0,0 +1,2 @@
+drop index definition_synonyms_synonym_idx;
+drop index synonyms_synonym_idx;
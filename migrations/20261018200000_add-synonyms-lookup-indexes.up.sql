@@ -0,0 +1,2 @@
+create index if not exists synonyms_synonym_idx on synonyms (normalize(lower(btrim(synonym)), NFC));
+create index if not exists definition_synonyms_synonym_idx on definition_synonyms (normalize(lower(btrim(synonym)), NFC));
@@ -0,0 +1 @@
+drop table missing_words;
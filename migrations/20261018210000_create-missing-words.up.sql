@@ -0,0 +1,4 @@
+create table if not exists missing_words (
+    word text primary key,
+    checked_at timestamptz not null default now()
+);
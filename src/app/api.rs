@@ -0,0 +1,235 @@
+use super::routes::{
+    add_word, get_words_page, refresh_word, related_words, required_word, reverse_lookup,
+    word_not_stored, ReverseBody,
+};
+use crate::{
+    error::{AppError, JsonError},
+    model::{
+        normalize_word, AddWordForm, MissingWord, PartOfSpeechCount, RelatedQuery, RelatedWords,
+        SearchQuery, StoredWord, WordsPage, WordsQuery,
+    },
+    provider::UpstreamMetrics,
+    repository::Repository,
+};
+use axum::{
+    debug_handler,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use log::info;
+use serde::Serialize;
+
+pub(super) fn initialize_router() -> Router<Repository> {
+    Router::new()
+        .route("/words", get(get_words).post(post_word))
+        .route("/random-words", get(get_random_words))
+        .route("/words/{word}", get(get_word).delete(delete_word))
+        .route("/words/{word}/refresh", post(post_refresh_word))
+        .route("/words/{word}/related", get(get_related_words))
+        .route("/parts-of-speech", get(get_parts_of_speech))
+        .route("/reverse", get(get_reverse))
+        .route("/upstream", get(get_upstream))
+        .route(
+            "/admin/missing-words",
+            get(get_missing_words).delete(clear_missing_words),
+        )
+        .route("/admin/missing-words/{word}", delete(delete_missing_word))
+        .fallback(handle_404)
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct WordsBody {
+    pub(super) words: Vec<String>,
+}
+
+/// Dictionary provider with its circuit breaker state and request counters.
+#[derive(Debug, Serialize)]
+struct UpstreamBody {
+    provider: &'static str,
+    #[serde(flatten)]
+    metrics: Option<UpstreamMetrics>,
+}
+
+#[derive(Debug, Serialize)]
+struct ClearedBody {
+    cleared: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct DeletedBody {
+    word: String,
+    deleted: bool,
+}
+
+#[debug_handler]
+async fn get_words(
+    State(state): State<Repository>,
+    Query(query): Query<WordsQuery>,
+) -> Result<Json<WordsPage>, JsonError> {
+    info!("Receive API request to list words");
+
+    let page = get_words_page(&state, &query).await?;
+
+    Ok(Json(page))
+}
+
+#[debug_handler]
+async fn get_random_words(State(state): State<Repository>) -> Result<Json<WordsBody>, JsonError> {
+    info!("Receive API request for random words");
+
+    let words = state.get_10_random_words().await?;
+
+    Ok(Json(WordsBody { words }))
+}
+
+#[debug_handler]
+async fn get_word(
+    State(state): State<Repository>,
+    Path(word): Path<String>,
+) -> Result<Json<StoredWord>, JsonError> {
+    let word = normalize_word(&word);
+    info!("Receive API request for information about word: '{word}'");
+    let Some(stored_word) = state.get_word_definitions(&word).await? else {
+        return Err(word_not_stored(&state, word).await.into());
+    };
+
+    Ok(Json(stored_word))
+}
+
+#[debug_handler]
+async fn post_word(
+    State(state): State<Repository>,
+    Json(form): Json<AddWordForm>,
+) -> Result<impl IntoResponse, JsonError> {
+    let word = required_word(&form.word)?;
+    info!("Receive API request to add definition for word: '{word}'");
+    let added = add_word(&state, &word).await?;
+
+    let Some(stored_word) = state.get_word_definitions(&word).await? else {
+        return Err(AppError::word_entries_not_found(word).into());
+    };
+
+    let code = if added {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+
+    Ok((code, Json(stored_word)))
+}
+
+#[debug_handler]
+async fn post_refresh_word(
+    State(state): State<Repository>,
+    Path(word): Path<String>,
+) -> Result<Json<StoredWord>, JsonError> {
+    let word = required_word(&word)?;
+    info!("Receive API request to refresh definitions for word: '{word}'");
+    refresh_word(&state, &word).await?;
+
+    let Some(stored_word) = state.get_word_definitions(&word).await? else {
+        return Err(AppError::word_entries_not_found(word).into());
+    };
+
+    Ok(Json(stored_word))
+}
+
+#[debug_handler]
+async fn delete_word(
+    State(state): State<Repository>,
+    Path(word): Path<String>,
+) -> Result<Json<DeletedBody>, JsonError> {
+    let word = required_word(&word)?;
+    info!("Receive API request to delete word: '{word}'");
+    let deleted = state.delete_word(&word).await?;
+
+    Ok(Json(DeletedBody { word, deleted }))
+}
+
+#[debug_handler]
+async fn get_related_words(
+    State(state): State<Repository>,
+    Path(word): Path<String>,
+    Query(query): Query<RelatedQuery>,
+) -> Result<Json<RelatedWords>, JsonError> {
+    let word = normalize_word(&word);
+    info!("Receive API request for words related to: '{word}'");
+
+    let related = related_words(&state, word, query.depth).await?;
+
+    Ok(Json(related))
+}
+
+#[debug_handler]
+async fn get_parts_of_speech(
+    State(state): State<Repository>,
+) -> Result<Json<Vec<PartOfSpeechCount>>, JsonError> {
+    info!("Receive API request for parts of speech distribution");
+
+    let parts_of_speech = state.get_parts_of_speech().await?;
+
+    Ok(Json(parts_of_speech))
+}
+
+#[debug_handler]
+async fn get_reverse(
+    State(state): State<Repository>,
+    Query(SearchQuery { q }): Query<SearchQuery>,
+) -> Result<Json<ReverseBody>, JsonError> {
+    let body = reverse_lookup(&state, q).await?;
+
+    Ok(Json(body))
+}
+
+#[debug_handler]
+async fn get_upstream(State(state): State<Repository>) -> Json<UpstreamBody> {
+    info!("Receive API request for upstream metrics");
+
+    Json(UpstreamBody {
+        provider: state.provider_name(),
+        metrics: state.provider_metrics(),
+    })
+}
+
+#[debug_handler]
+async fn get_missing_words(
+    State(state): State<Repository>,
+) -> Result<Json<Vec<MissingWord>>, JsonError> {
+    info!("Receive API request for words without definitions");
+
+    let missing_words = state.get_missing_words().await?;
+
+    Ok(Json(missing_words))
+}
+
+#[debug_handler]
+async fn clear_missing_words(
+    State(state): State<Repository>,
+) -> Result<Json<ClearedBody>, JsonError> {
+    info!("Receive API request to clear words without definitions");
+    let cleared = state.clear_missing_words().await?;
+
+    Ok(Json(ClearedBody { cleared }))
+}
+
+#[debug_handler]
+async fn delete_missing_word(
+    State(state): State<Repository>,
+    Path(word): Path<String>,
+) -> Result<Json<DeletedBody>, JsonError> {
+    let word = normalize_word(&word);
+    info!("Receive API request to forget missing word: '{word}'");
+    let deleted = state.delete_missing_word(&word).await?;
+
+    Ok(Json(DeletedBody { word, deleted }))
+}
+
+#[debug_handler]
+async fn handle_404(_: State<Repository>) -> JsonError {
+    info!("User tried to access non-existing API endpoint");
+
+    AppError::page_not_found().into()
+}
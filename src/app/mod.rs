@@ -1,9 +1,10 @@
-use crate::{repository, Config};
+use crate::{provider, repository, Config};
 use anyhow::Result;
 use axum::Router;
 use log::info;
 use tokio::net::TcpListener;
 
+mod api;
 mod routes;
 
 pub(crate) struct App {
@@ -19,7 +20,15 @@ impl App {
             config.address.0, config.address.1
         );
 
-        let shared_state = repository::Repository::initialize(config.database_url).await?;
+        let provider = provider::initialize(config.provider, &config.rate_limit)?;
+        info!("Dictionary provider initialized: {}", provider.name());
+
+        let shared_state = repository::Repository::initialize(
+            config.database_url,
+            provider,
+            config.missing_words_ttl,
+        )
+        .await?;
         info!("Repository initialized");
 
         let router = routes::initialize_router(shared_state);
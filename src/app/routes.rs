@@ -1,18 +1,29 @@
+use super::api::{self, WordsBody};
 use crate::{
-    error::AppError,
-    model::{AddWordForm, WordEntry},
+    error::{self, AppError},
+    format::ResponseFormat,
+    model::{
+        normalize_word, AddWordForm, AutocompleteQuery, MissingWord, PartOfSpeechCount,
+        RelatedNode, RelatedQuery, RelatedWords, Relation, ReverseHit, SearchHit, SearchQuery,
+        StoredWord, WordEntry, WordsPage, WordsQuery, WordsSort,
+    },
     repository::Repository,
 };
 use askama_axum::{into_response, Template};
 use axum::{
     debug_handler,
-    extract::{Path, State},
-    response::{IntoResponse, Redirect},
+    extract::{Path, Query, State},
+    middleware,
+    response::{IntoResponse, Redirect, Response},
     routing::{get, post},
-    Form, Router,
+    Form, Json, Router,
 };
+use chrono::{DateTime, Utc};
 use log::{error, info};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use tower_http::services::ServeDir;
+use url::form_urlencoded;
 
 pub(crate) fn initialize_router(shared_state: Repository) -> Router {
     Router::new()
@@ -20,8 +31,23 @@ pub(crate) fn initialize_router(shared_state: Repository) -> Router {
         .route("/words", post(post_word))
         .route("/words", get(get_words))
         .route("/words/{word}", get(get_word))
+        .route("/words/{word}/refresh", post(post_refresh_word))
+        .route("/words/{word}/delete", post(post_delete_word))
+        .route("/words/{word}/related", get(get_related_words))
+        .route("/search", get(get_search))
+        .route("/reverse", get(get_reverse))
+        .route("/parts-of-speech", get(get_parts_of_speech))
+        .route("/admin/missing-words", get(get_missing_words))
+        .route("/admin/missing-words/clear", post(post_clear_missing_words))
+        .route(
+            "/admin/missing-words/{word}/delete",
+            post(post_delete_missing_word),
+        )
+        .route("/autocomplete", get(get_autocomplete))
+        .nest("/api/v1", api::initialize_router())
         .nest_service("/static", ServeDir::new("templates"))
         .fallback(handle_404)
+        .layer(middleware::from_fn(error::negotiate_error))
         .with_state(shared_state)
 }
 
@@ -45,6 +71,21 @@ async fn get_index(State(state): State<Repository>) -> Result<impl IntoResponse,
 #[derive(Debug, Template)]
 #[template(path = "word.askama.html")]
 struct WordTemplate {
+    word: String,
+    fetched_at: DateTime<Utc>,
+    word_entries: Vec<WordEntry>,
+    stored: HashSet<String>,
+}
+
+impl WordTemplate {
+    fn is_stored(&self, word: &str) -> bool {
+        self.stored.contains(&normalize_word(word))
+    }
+}
+
+#[derive(Debug, Template)]
+#[template(path = "word.askama.txt")]
+struct WordTextTemplate {
     word: String,
     word_entries: Vec<WordEntry>,
 }
@@ -52,16 +93,179 @@ struct WordTemplate {
 #[debug_handler]
 async fn get_word(
     State(state): State<Repository>,
+    format: ResponseFormat,
     Path(word): Path<String>,
-) -> Result<impl IntoResponse, AppError> {
+) -> Result<Response, AppError> {
+    let word = normalize_word(&word);
     info!("Receive request for information about word: '{word}'");
-    let Some(word_entries) = state.get_word_definitions(&word).await? else {
-        return Err(AppError::word_entries_not_found(word));
+    let Some(stored_word) = state.get_word_definitions(&word).await? else {
+        return Err(word_not_stored(&state, word).await);
     };
 
-    let html = WordTemplate { word, word_entries };
+    let response = match format {
+        ResponseFormat::Html => {
+            let StoredWord {
+                word,
+                fetched_at,
+                entries,
+            } = stored_word;
 
-    Ok(into_response(&html))
+            let related = related_words_of(&entries);
+            let stored = state.get_stored_words(&related).await?;
+
+            let html = WordTemplate {
+                word,
+                fetched_at,
+                word_entries: entries,
+                stored,
+            };
+
+            into_response(&html).into_response()
+        }
+        ResponseFormat::Json => Json(stored_word).into_response(),
+        ResponseFormat::Text => {
+            let text = WordTextTemplate {
+                word: stored_word.word,
+                word_entries: stored_word.entries,
+            };
+
+            into_response(&text).into_response()
+        }
+    };
+
+    Ok(response)
+}
+
+/// Normalized synonyms and antonyms mentioned anywhere in the entries.
+fn related_words_of(entries: &[WordEntry]) -> Vec<String> {
+    let mut related = HashSet::new();
+
+    for meaning in entries.iter().flat_map(|entry| &entry.meanings) {
+        let definitions = meaning.definitions.iter();
+        let words = meaning.synonyms.iter().chain(&meaning.antonyms).chain(
+            definitions
+                .flat_map(|definition| definition.synonyms.iter().chain(&definition.antonyms)),
+        );
+
+        related.extend(words.map(|word| normalize_word(word)));
+    }
+
+    related.into_iter().collect()
+}
+
+#[derive(Debug, Template)]
+#[template(path = "related.askama.html")]
+struct RelatedTemplate {
+    related: RelatedWords,
+    depths: Vec<i32>,
+}
+
+impl RelatedTemplate {
+    fn is_depth(&self, depth: &i32) -> bool {
+        self.related.depth == *depth
+    }
+
+    fn is_stored(&self, word: &str) -> bool {
+        self.related
+            .nodes
+            .iter()
+            .any(|node| node.stored && node.word == word)
+    }
+}
+
+#[debug_handler]
+async fn get_related_words(
+    State(state): State<Repository>,
+    format: ResponseFormat,
+    Path(word): Path<String>,
+    Query(query): Query<RelatedQuery>,
+) -> Result<Response, AppError> {
+    let word = normalize_word(&word);
+    info!("Receive request for words related to: '{word}'");
+
+    let related = related_words(&state, word, query.depth).await?;
+
+    let response = match format {
+        ResponseFormat::Html => {
+            let html = RelatedTemplate {
+                related,
+                depths: (1..=RELATED_MAX_DEPTH).collect(),
+            };
+
+            into_response(&html).into_response()
+        }
+        ResponseFormat::Json => Json(related).into_response(),
+        ResponseFormat::Text => {
+            let mut text = String::new();
+            for edge in related.edges {
+                let relation = match edge.relation {
+                    Relation::Synonym => "synonym",
+                    Relation::Antonym => "antonym",
+                };
+                text.push_str(&format!("{}\t{relation}\t{}\n", edge.from, edge.to));
+            }
+            text.into_response()
+        }
+    };
+
+    Ok(response)
+}
+
+const RELATED_DEPTH: i32 = 1;
+const RELATED_MAX_DEPTH: i32 = 3;
+
+/// Walks synonyms and antonyms of a stored word up to `depth` hops.
+pub(super) async fn related_words(
+    state: &Repository,
+    word: String,
+    depth: Option<i32>,
+) -> Result<RelatedWords, AppError> {
+    if !state.word_exists(&word).await? {
+        return Err(word_not_stored(state, word).await);
+    }
+
+    let depth = depth.unwrap_or(RELATED_DEPTH).clamp(1, RELATED_MAX_DEPTH);
+    let edges = state.get_related_words(&word, depth).await?;
+
+    // Edges come ordered by hops, so the first time a word is seen is the closest.
+    let mut hops = HashMap::from([(word.clone(), 0)]);
+    let mut words = vec![word.clone()];
+    for edge in &edges {
+        if !hops.contains_key(&edge.to) {
+            hops.insert(edge.to.clone(), edge.hops);
+            words.push(edge.to.clone());
+        }
+    }
+
+    let stored = state.get_stored_words(&words).await?;
+    let nodes = words
+        .into_iter()
+        .map(|word| RelatedNode {
+            hops: hops[&word],
+            stored: stored.contains(&word),
+            word,
+        })
+        .collect();
+
+    Ok(RelatedWords {
+        word,
+        depth,
+        nodes,
+        edges,
+    })
+}
+
+const SUGGESTIONS_LIMIT: i64 = 5;
+
+/// Not found error for a word, suggesting similar stored words.
+pub(super) async fn word_not_stored(state: &Repository, word: String) -> AppError {
+    match state.get_similar_words(&word, SUGGESTIONS_LIMIT).await {
+        Ok(suggestions) => AppError::word_entries_not_found_with_suggestions(word, suggestions),
+        Err(err) => {
+            error!("Cannot find similar words for '{word}': {err:?}");
+            AppError::word_entries_not_found(word)
+        }
+    }
 }
 
 #[debug_handler]
@@ -69,41 +273,472 @@ async fn post_word(
     State(state): State<Repository>,
     Form(form): Form<AddWordForm>,
 ) -> Result<impl IntoResponse, AppError> {
-    let word = form.word;
+    let word = required_word(&form.word)?;
     info!("Receive request to add definition for word: '{word}'");
-    let word_definitions = state.request_word_definitions(&word).await?;
-    info!("Received definition from Dictionary API for word: '{word}'");
+    add_word(&state, &word).await?;
+
+    Ok(Redirect::to(&format!("/words/{word}")))
+}
+
+/// Normalizes a word that is added, refreshed or deleted, it must not be blank.
+pub(super) fn required_word(word: &str) -> Result<String, AppError> {
+    let word = normalize_word(word);
+    if word.is_empty() {
+        return Err(AppError::empty_word());
+    }
 
-    let word_definitions = match word_definitions {
-        crate::model::ApiResponse::Success(words_entries) => words_entries,
-        crate::model::ApiResponse::Missing(crate::model::MissingResponse { .. }) => {
-            error!("No definitions found for word: '{word}'");
-            return Err(AppError::word_definitions_not_found(word));
+    Ok(word)
+}
+
+/// Fetches and stores definitions for a word, returns `false` if it was already stored.
+pub(super) async fn add_word(state: &Repository, word: &str) -> Result<bool, AppError> {
+    if state.word_exists(word).await? {
+        if state.get_word_definitions(word).await?.is_some() {
+            info!("Word is already stored, skipping provider request: '{word}'");
+            return Ok(false);
         }
+
+        // A word stored without entries cannot be shown, request it again.
+        info!("Word is stored without definitions, refreshing it: '{word}'");
+        refresh_word(state, word).await?;
+        return Ok(true);
+    }
+
+    if state.is_word_missing(word).await? {
+        info!("Word is known to have no definitions, skipping provider request: '{word}'");
+        return Err(AppError::word_definitions_not_found(word.to_owned()));
+    }
+
+    let word_definitions = state.request_word_definitions(word).await?;
+    info!(
+        "Received definition from {} for word: '{word}'",
+        state.provider_name()
+    );
+
+    let Some(word_definitions) = word_definitions.filter(|entries| !entries.is_empty()) else {
+        error!("No definitions found for word: '{word}'");
+        state.add_missing_word(word).await?;
+        return Err(AppError::word_definitions_not_found(word.to_owned()));
     };
 
     info!("Adding definitions to database for word: '{word}'");
-    state.add_word_entries(&word, word_definitions).await?;
-    info!("Successfully added definitions to database for word: '{word}'");
+    let added = state.add_word_entries(word, word_definitions).await?;
+    if added {
+        info!("Successfully added definitions to database for word: '{word}'");
+    } else {
+        info!("Word was stored concurrently, discarding definitions: '{word}'");
+    }
+
+    Ok(added)
+}
+
+#[debug_handler]
+async fn post_refresh_word(
+    State(state): State<Repository>,
+    Path(word): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let word = required_word(&word)?;
+    info!("Receive request to refresh definitions for word: '{word}'");
+    refresh_word(&state, &word).await?;
 
     Ok(Redirect::to(&format!("/words/{word}")))
 }
 
+pub(super) async fn refresh_word(state: &Repository, word: &str) -> Result<(), AppError> {
+    match state.refresh_word(word).await? {
+        None => Err(AppError::word_entries_not_found(word.to_owned())),
+        Some(false) => {
+            error!("No definitions found anymore for word: '{word}'");
+            Err(AppError::word_definitions_not_found(word.to_owned()))
+        }
+        Some(true) => {
+            info!("Successfully refreshed definitions for word: '{word}'");
+            Ok(())
+        }
+    }
+}
+
+#[debug_handler]
+async fn post_delete_word(
+    State(state): State<Repository>,
+    Path(word): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let word = required_word(&word)?;
+    info!("Receive request to delete word: '{word}'");
+    if !state.delete_word(&word).await? {
+        return Err(AppError::word_entries_not_found(word));
+    }
+    info!("Successfully deleted word: '{word}'");
+
+    Ok(Redirect::to("/words"))
+}
+
 #[derive(Debug, Template)]
 #[template(path = "words.askama.html")]
 struct WordsTemplate {
-    words: Vec<String>,
+    page: WordsPage,
+    sort: WordsSort,
+    letter: Option<char>,
+    pos: Option<String>,
+    letters: Vec<char>,
+    query_after: bool,
+}
+
+impl WordsTemplate {
+    /// Link to the word list that keeps the part of speech filter.
+    fn href(&self, sort: WordsSort, letter: Option<&char>, after: Option<&str>) -> String {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        query.append_pair("sort", sort.as_str());
+        if let Some(letter) = letter {
+            query.append_pair("letter", &letter.to_string());
+        }
+        if let Some(pos) = &self.pos {
+            query.append_pair("pos", pos);
+        }
+        if let Some(after) = after {
+            query.append_pair("after", after);
+        }
+
+        format!("/words?{}", query.finish())
+    }
 }
 
 #[debug_handler]
-async fn get_words(State(state): State<Repository>) -> Result<impl IntoResponse, AppError> {
-    info!("Receive request to list all words");
+async fn get_words(
+    State(state): State<Repository>,
+    format: ResponseFormat,
+    Query(query): Query<WordsQuery>,
+) -> Result<Response, AppError> {
+    info!("Receive request to list words");
 
-    let words = state.get_all_words().await?;
+    let page = get_words_page(&state, &query).await?;
 
-    let html = WordsTemplate { words };
+    let response = match format {
+        ResponseFormat::Html => {
+            let html = WordsTemplate {
+                page,
+                sort: query.sort,
+                letter: query.letter.map(lowercase_letter),
+                pos: part_of_speech(&query),
+                letters: ('a'..='z').collect(),
+                query_after: query.after.is_some(),
+            };
 
-    Ok(into_response(&html))
+            into_response(&html).into_response()
+        }
+        ResponseFormat::Json => Json(page).into_response(),
+        ResponseFormat::Text => {
+            let mut text = String::new();
+            for word in page.words {
+                text.push_str(&word);
+                text.push('\n');
+            }
+            text.into_response()
+        }
+    };
+
+    Ok(response)
+}
+
+const WORDS_PAGE_LIMIT: i64 = 50;
+const WORDS_PAGE_MAX_LIMIT: i64 = 200;
+
+/// Loads one page of the word list with keyset pagination.
+pub(super) async fn get_words_page(
+    state: &Repository,
+    query: &WordsQuery,
+) -> Result<WordsPage, AppError> {
+    let limit = query
+        .limit
+        .unwrap_or(WORDS_PAGE_LIMIT)
+        .clamp(1, WORDS_PAGE_MAX_LIMIT);
+    let page_size = usize::try_from(limit)?;
+    let letter = query.letter.map(lowercase_letter);
+    let pos = part_of_speech(query);
+    let pos = pos.as_deref();
+
+    let total = state.count_words(letter, pos).await?;
+
+    // One extra row tells whether there is a next page.
+    let (words, next) = match query.sort {
+        WordsSort::Alphabetical => {
+            let mut words = state
+                .get_words_alphabetical(letter, pos, query.after.as_deref(), limit + 1)
+                .await?;
+
+            let next = (words.len() > page_size).then(|| {
+                words.truncate(page_size);
+                words.last().cloned().unwrap_or_default()
+            });
+
+            (words, next)
+        }
+        WordsSort::Recent => {
+            let after = query
+                .after
+                .as_deref()
+                .map(|after| {
+                    after
+                        .parse::<i32>()
+                        .map_err(|_| AppError::invalid_cursor(after.to_owned()))
+                })
+                .transpose()?;
+
+            let mut words = state
+                .get_words_recent(letter, pos, after, limit + 1)
+                .await?;
+
+            let next = (words.len() > page_size).then(|| {
+                words.truncate(page_size);
+                words
+                    .last()
+                    .map(|(id, _)| id.to_string())
+                    .unwrap_or_default()
+            });
+
+            (words.into_iter().map(|(_, word)| word).collect(), next)
+        }
+    };
+
+    Ok(WordsPage { words, total, next })
+}
+
+fn lowercase_letter(letter: char) -> char {
+    letter.to_lowercase().next().unwrap_or(letter)
+}
+
+fn part_of_speech(query: &WordsQuery) -> Option<String> {
+    let pos = query.pos.as_deref()?.trim().to_lowercase();
+
+    (!pos.is_empty()).then_some(pos)
+}
+
+#[derive(Debug, Template)]
+#[template(path = "missing_words.askama.html")]
+struct MissingWordsTemplate {
+    missing_words: Vec<MissingWord>,
+}
+
+#[debug_handler]
+async fn get_missing_words(
+    State(state): State<Repository>,
+    format: ResponseFormat,
+) -> Result<Response, AppError> {
+    info!("Receive request for words without definitions");
+
+    let missing_words = state.get_missing_words().await?;
+
+    let response = match format {
+        ResponseFormat::Html => {
+            into_response(&MissingWordsTemplate { missing_words }).into_response()
+        }
+        ResponseFormat::Json => Json(missing_words).into_response(),
+        ResponseFormat::Text => {
+            let mut text = String::new();
+            for missing_word in missing_words {
+                text.push_str(&format!(
+                    "{}\t{}\n",
+                    missing_word.word,
+                    missing_word.expires_at.format("%Y-%m-%d %H:%M UTC")
+                ));
+            }
+            text.into_response()
+        }
+    };
+
+    Ok(response)
+}
+
+#[debug_handler]
+async fn post_clear_missing_words(
+    State(state): State<Repository>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("Receive request to clear words without definitions");
+    let cleared = state.clear_missing_words().await?;
+    info!("Cleared {cleared} words without definitions");
+
+    Ok(Redirect::to("/admin/missing-words"))
+}
+
+#[debug_handler]
+async fn post_delete_missing_word(
+    State(state): State<Repository>,
+    Path(word): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let word = normalize_word(&word);
+    info!("Receive request to forget missing word: '{word}'");
+    if !state.delete_missing_word(&word).await? {
+        return Err(AppError::page_not_found());
+    }
+
+    Ok(Redirect::to("/admin/missing-words"))
+}
+
+#[derive(Debug, Template)]
+#[template(path = "parts_of_speech.askama.html")]
+struct PartsOfSpeechTemplate {
+    parts_of_speech: Vec<PartOfSpeechCount>,
+}
+
+#[debug_handler]
+async fn get_parts_of_speech(
+    State(state): State<Repository>,
+    format: ResponseFormat,
+) -> Result<Response, AppError> {
+    info!("Receive request for parts of speech distribution");
+
+    let parts_of_speech = state.get_parts_of_speech().await?;
+
+    let response = match format {
+        ResponseFormat::Html => {
+            let html = PartsOfSpeechTemplate { parts_of_speech };
+
+            into_response(&html).into_response()
+        }
+        ResponseFormat::Json => Json(parts_of_speech).into_response(),
+        ResponseFormat::Text => {
+            let mut text = String::new();
+            for part_of_speech in parts_of_speech {
+                text.push_str(&format!(
+                    "{}\t{}\t{}\n",
+                    part_of_speech.part_of_speech, part_of_speech.words, part_of_speech.meanings
+                ));
+            }
+            text.into_response()
+        }
+    };
+
+    Ok(response)
+}
+
+#[derive(Debug, Template)]
+#[template(path = "search.askama.html")]
+struct SearchTemplate {
+    query: String,
+    hits: Vec<SearchHit>,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchBody {
+    query: String,
+    hits: Vec<SearchHit>,
+}
+
+const SEARCH_LIMIT: i64 = 50;
+
+#[debug_handler]
+async fn get_search(
+    State(state): State<Repository>,
+    format: ResponseFormat,
+    Query(SearchQuery { q }): Query<SearchQuery>,
+) -> Result<Response, AppError> {
+    let query = q.unwrap_or_default().trim().to_owned();
+    info!("Receive request to search definitions: '{query}'");
+
+    let hits = if query.is_empty() {
+        Vec::new()
+    } else {
+        state.search_definitions(&query, SEARCH_LIMIT).await?
+    };
+
+    let response = match format {
+        ResponseFormat::Html => into_response(&SearchTemplate { query, hits }).into_response(),
+        ResponseFormat::Json => Json(SearchBody { query, hits }).into_response(),
+        ResponseFormat::Text => {
+            let mut text = String::new();
+            for hit in hits {
+                let definition: String = hit.definition.into_iter().map(|part| part.text).collect();
+                text.push_str(&format!(
+                    "{} ({}): {definition}\n",
+                    hit.word, hit.part_of_speech
+                ));
+            }
+            text.into_response()
+        }
+    };
+
+    Ok(response)
+}
+
+#[derive(Debug, Template)]
+#[template(path = "reverse.askama.html")]
+struct ReverseTemplate {
+    query: String,
+    hits: Vec<ReverseHit>,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct ReverseBody {
+    query: String,
+    hits: Vec<ReverseHit>,
+}
+
+const REVERSE_LIMIT: i64 = 50;
+
+#[debug_handler]
+async fn get_reverse(
+    State(state): State<Repository>,
+    format: ResponseFormat,
+    Query(SearchQuery { q }): Query<SearchQuery>,
+) -> Result<Response, AppError> {
+    let ReverseBody { query, hits } = reverse_lookup(&state, q).await?;
+
+    let response = match format {
+        ResponseFormat::Html => into_response(&ReverseTemplate { query, hits }).into_response(),
+        ResponseFormat::Json => Json(ReverseBody { query, hits }).into_response(),
+        ResponseFormat::Text => {
+            let mut text = String::new();
+            for hit in hits {
+                text.push_str(&hit.word);
+                if let Some(definition) = hit.definition {
+                    text.push_str(&format!(": {definition}"));
+                }
+                text.push('\n');
+            }
+            text.into_response()
+        }
+    };
+
+    Ok(response)
+}
+
+/// Finds words meaning `q`, an empty query finds nothing.
+pub(super) async fn reverse_lookup(
+    state: &Repository,
+    q: Option<String>,
+) -> Result<ReverseBody, AppError> {
+    let query = q.unwrap_or_default().trim().to_owned();
+    info!("Receive request for words meaning: '{query}'");
+
+    let hits = if query.is_empty() {
+        Vec::new()
+    } else {
+        state.reverse_lookup(&query, REVERSE_LIMIT).await?
+    };
+
+    Ok(ReverseBody { query, hits })
+}
+
+const AUTOCOMPLETE_LIMIT: i64 = 10;
+const AUTOCOMPLETE_MAX_LIMIT: i64 = 50;
+
+#[debug_handler]
+async fn get_autocomplete(
+    State(state): State<Repository>,
+    Query(AutocompleteQuery { prefix, limit }): Query<AutocompleteQuery>,
+) -> Result<Json<WordsBody>, AppError> {
+    let prefix = normalize_word(&prefix.unwrap_or_default());
+    let limit = limit
+        .unwrap_or(AUTOCOMPLETE_LIMIT)
+        .clamp(1, AUTOCOMPLETE_MAX_LIMIT);
+
+    if prefix.is_empty() {
+        return Ok(Json(WordsBody { words: Vec::new() }));
+    }
+
+    let words = state.get_words_by_prefix(&prefix, limit).await?;
+
+    Ok(Json(WordsBody { words }))
 }
 
 #[debug_handler]
@@ -1,6 +1,14 @@
+use crate::{format::ResponseFormat, provider::UpstreamError};
 use askama::Template;
-use axum::{http::StatusCode, response::IntoResponse};
-use log::error;
+use axum::{
+    extract::Request,
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use log::{error, warn};
+use serde::Serialize;
 
 pub(crate) struct AppError {
     code: StatusCode,
@@ -12,6 +20,26 @@ pub(crate) struct AppError {
 pub(crate) struct ErrorTemplate {
     code: StatusCode,
     message: String,
+    word: Option<String>,
+    suggestions: Vec<String>,
+    retry_after: Option<u64>,
+}
+
+/// Error rendered as a JSON body for the `/api/v1` routes.
+pub(crate) struct JsonError(AppError);
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ErrorBody {
+    code: u16,
+    error: &'static str,
+    message: String,
+    /// Word that is not stored yet and can be added from the dictionary provider.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    word: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<String>,
+    #[serde(rename = "retryAfter", skip_serializing_if = "Option::is_none")]
+    retry_after: Option<u64>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -19,16 +47,58 @@ pub(crate) enum ErrorKind {
     #[error("Cannot find definitions for word: '{0}'")]
     NoDefinitionsFound(String),
 
-    #[error("There are no records found in dictionary for word: '{0}'")]
-    NoEntriesFound(String),
+    #[error("There are no records found in dictionary for word: '{word}'")]
+    NoEntriesFound {
+        word: String,
+        suggestions: Vec<String>,
+    },
 
     #[error("The requested page does not exist.")]
     PageNotFound,
 
+    #[error("Invalid page cursor: '{0}'")]
+    InvalidCursor(String),
+
+    #[error("The word must not be empty.")]
+    EmptyWord,
+
+    #[error("The dictionary service is unavailable right now, please try again later.")]
+    UpstreamUnavailable,
+
+    #[error("The dictionary service is receiving too many requests, please try again later.")]
+    UpstreamRateLimited { retry_after: Option<u64> },
+
+    #[error("The dictionary service returned an unexpected response.")]
+    UpstreamMalformedResponse,
+
+    #[error("The dictionary service took too long to respond, please try again.")]
+    UpstreamTimeout,
+
+    #[error("Too many words are being looked up right now, please try again later.")]
+    UpstreamThrottled { retry_after: u64 },
+
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
+impl ErrorKind {
+    fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::NoDefinitionsFound(_) => "no_definitions_found",
+            ErrorKind::NoEntriesFound { .. } => "no_entries_found",
+            ErrorKind::PageNotFound => "page_not_found",
+            ErrorKind::InvalidCursor(_) => "invalid_cursor",
+            ErrorKind::EmptyWord => "empty_word",
+            ErrorKind::UpstreamUnavailable => "upstream_unavailable",
+            ErrorKind::UpstreamRateLimited { .. } => "upstream_rate_limited",
+            ErrorKind::UpstreamMalformedResponse => "upstream_malformed_response",
+            ErrorKind::UpstreamTimeout => "upstream_timeout",
+            ErrorKind::UpstreamThrottled { .. } => "upstream_throttled",
+            ErrorKind::Other(_) => "internal",
+        }
+    }
+}
+
 impl AppError {
     pub(crate) fn new(code: StatusCode, kind: ErrorKind) -> Self {
         Self { code, kind }
@@ -38,29 +108,125 @@ impl AppError {
         Self::new(StatusCode::NOT_FOUND, ErrorKind::NoDefinitionsFound(word))
     }
 
-    pub(crate) fn word_entries_not_found(message: String) -> Self {
-        Self::new(StatusCode::NOT_FOUND, ErrorKind::NoEntriesFound(message))
+    pub(crate) fn word_entries_not_found(word: String) -> Self {
+        Self::word_entries_not_found_with_suggestions(word, Vec::new())
+    }
+
+    pub(crate) fn word_entries_not_found_with_suggestions(
+        word: String,
+        suggestions: Vec<String>,
+    ) -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            ErrorKind::NoEntriesFound { word, suggestions },
+        )
     }
 
     pub(crate) fn page_not_found() -> Self {
         Self::new(StatusCode::NOT_FOUND, ErrorKind::PageNotFound)
     }
-}
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> axum::response::Response {
-        let code = self.code;
-        let message = match self.kind {
+    pub(crate) fn invalid_cursor(cursor: String) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, ErrorKind::InvalidCursor(cursor))
+    }
+
+    pub(crate) fn empty_word() -> Self {
+        Self::new(StatusCode::BAD_REQUEST, ErrorKind::EmptyWord)
+    }
+
+    /// Seconds to wait before retrying, sent as `Retry-After`.
+    fn retry_after(&self) -> Option<u64> {
+        match self.kind {
+            ErrorKind::UpstreamRateLimited { retry_after } => retry_after,
+            ErrorKind::UpstreamThrottled { retry_after } => Some(retry_after),
+            _ => None,
+        }
+    }
+
+    fn into_body(self) -> ErrorBody {
+        let error = self.kind.name();
+        let retry_after = self.retry_after();
+        let message = match &self.kind {
             ErrorKind::Other(error) => {
                 error!("{error:?}");
                 "Something went wrong.".to_owned()
             }
             _ => self.kind.to_string(),
         };
+        let (word, suggestions) = match self.kind {
+            ErrorKind::NoEntriesFound { word, suggestions } => (Some(word), suggestions),
+            _ => (None, Vec::new()),
+        };
 
-        let template = ErrorTemplate { code, message };
+        ErrorBody {
+            code: self.code.as_u16(),
+            error,
+            message,
+            word,
+            suggestions,
+            retry_after,
+        }
+    }
+}
 
-        (code, askama_axum::into_response(&template)).into_response()
+/// Renders the error page as HTML and keeps the [`ErrorBody`] in the response
+/// extensions, so that [`negotiate_error`] can re-render it for other formats.
+impl IntoResponse for AppError {
+    fn into_response(self) -> axum::response::Response {
+        let code = self.code;
+        let body = self.into_body();
+
+        let template = ErrorTemplate {
+            code,
+            message: body.message.clone(),
+            word: body.word.clone(),
+            suggestions: body.suggestions.clone(),
+            retry_after: body.retry_after,
+        };
+
+        let mut response = (code, askama_axum::into_response(&template)).into_response();
+        insert_retry_after(&mut response, body.retry_after);
+        response.extensions_mut().insert(body);
+
+        response
+    }
+}
+
+/// Middleware rendering [`AppError`] responses in the format requested by `Accept`.
+pub(crate) async fn negotiate_error(
+    format: ResponseFormat,
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+
+    let Some(body) = response.extensions().get::<ErrorBody>().cloned() else {
+        return response;
+    };
+
+    let code = response.status();
+    let retry_after = body.retry_after;
+    let mut response = match format {
+        ResponseFormat::Html => return response,
+        ResponseFormat::Json => (code, Json(body)).into_response(),
+        ResponseFormat::Text => {
+            let mut text = format!("{code}\n{}\n", body.message);
+            if !body.suggestions.is_empty() {
+                text.push_str(&format!("Did you mean: {}\n", body.suggestions.join(", ")));
+            }
+            (code, text).into_response()
+        }
+    };
+    insert_retry_after(&mut response, retry_after);
+
+    response
+}
+
+fn insert_retry_after(response: &mut Response, retry_after: Option<u64>) {
+    if let Some(retry_after) = retry_after {
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(retry_after));
     }
 }
 
@@ -69,9 +235,62 @@ where
     E: Into<anyhow::Error>,
 {
     fn from(err: E) -> Self {
-        Self {
-            code: StatusCode::INTERNAL_SERVER_ERROR,
-            kind: ErrorKind::Other(err.into()),
-        }
+        let err = err.into();
+
+        let Some(upstream) = err
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<UpstreamError>())
+        else {
+            return Self::new(StatusCode::INTERNAL_SERVER_ERROR, ErrorKind::Other(err));
+        };
+
+        warn!("{err:?}");
+        let (code, kind) = match upstream {
+            UpstreamError::Unavailable(_) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorKind::UpstreamUnavailable,
+            ),
+            UpstreamError::RateLimited { retry_after } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                ErrorKind::UpstreamRateLimited {
+                    retry_after: retry_after.map(|retry_after| retry_after.as_secs()),
+                },
+            ),
+            UpstreamError::MalformedResponse(_) => (
+                StatusCode::BAD_GATEWAY,
+                ErrorKind::UpstreamMalformedResponse,
+            ),
+            UpstreamError::Timeout => (StatusCode::GATEWAY_TIMEOUT, ErrorKind::UpstreamTimeout),
+            UpstreamError::Throttled { retry_after } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorKind::UpstreamThrottled {
+                    // Round up, retrying earlier would be rejected again.
+                    retry_after: retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0),
+                },
+            ),
+        };
+
+        Self::new(code, kind)
+    }
+}
+
+impl IntoResponse for JsonError {
+    fn into_response(self) -> axum::response::Response {
+        let code = self.0.code;
+        let retry_after = self.0.retry_after();
+
+        let mut response = (code, Json(self.0.into_body())).into_response();
+        insert_retry_after(&mut response, retry_after);
+
+        response
+    }
+}
+
+impl<E> From<E> for JsonError
+where
+    E: Into<AppError>,
+{
+    fn from(err: E) -> Self {
+        Self(err.into())
     }
 }
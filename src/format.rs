@@ -0,0 +1,64 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header::ACCEPT, request::Parts, HeaderMap},
+};
+
+/// Representation requested by the client through the `Accept` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ResponseFormat {
+    Html,
+    Json,
+    Text,
+}
+
+impl ResponseFormat {
+    /// Picks the supported media type with the highest quality value.
+    ///
+    /// Wildcards alone (`*/*`, as sent by curl) or a missing header select JSON,
+    /// browsers always list `text/html` explicitly.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        let mut best: Option<(Self, f32)> = None;
+
+        for value in headers.get_all(ACCEPT) {
+            let Ok(value) = value.to_str() else {
+                continue;
+            };
+
+            for media_range in value.split(',') {
+                let mut parts = media_range.split(';');
+                let media_type = parts.next().unwrap_or_default().trim();
+
+                let quality = parts
+                    .filter_map(|parameter| parameter.trim().strip_prefix("q="))
+                    .find_map(|quality| quality.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+
+                let format = match media_type.to_ascii_lowercase().as_str() {
+                    "text/html" | "application/xhtml+xml" => Self::Html,
+                    "application/json" | "application/*" => Self::Json,
+                    "text/plain" => Self::Text,
+                    _ => continue,
+                };
+
+                if quality > 0.0 && best.is_none_or(|(_, best_quality)| quality > best_quality) {
+                    best = Some((format, quality));
+                }
+            }
+        }
+
+        best.map_or(Self::Json, |(format, _)| format)
+    }
+}
+
+impl<S> FromRequestParts<S> for ResponseFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
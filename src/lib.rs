@@ -1,11 +1,13 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use log::info;
-use std::net::Ipv4Addr;
+use std::{net::Ipv4Addr, path::PathBuf, str::FromStr, time::Duration};
 use url::Url;
 
 mod app;
 mod error;
+mod format;
 mod model;
+mod provider;
 mod repository;
 
 pub async fn run() -> Result<()> {
@@ -24,6 +26,111 @@ pub async fn run() -> Result<()> {
 pub(crate) struct Config {
     address: (Ipv4Addr, u16),
     database_url: Url,
+    provider: ProviderConfig,
+    rate_limit: RateLimitConfig,
+    /// How long a word without definitions is not requested again.
+    missing_words_ttl: Duration,
+}
+
+/// Token bucket shared by all requests to the dictionary provider.
+pub(crate) struct RateLimitConfig {
+    /// Tokens added per second.
+    rate: f64,
+    /// Bucket capacity, the largest burst of requests sent at once.
+    burst: u32,
+    /// Longest time a request waits for a token before it is rejected.
+    max_wait: Duration,
+}
+
+impl RateLimitConfig {
+    fn new() -> Result<Self> {
+        let rate: f64 = env_var_or("UPSTREAM_RATE_PER_SEC", 5.0)?;
+        if !(rate > 0.0 && rate.is_finite()) {
+            bail!("Invalid UPSTREAM_RATE_PER_SEC: '{rate}'");
+        }
+        let burst = env_var_or("UPSTREAM_BURST", 10)?.max(1);
+        let max_wait = Duration::from_millis(env_var_or("UPSTREAM_MAX_WAIT_MS", 2000)?);
+
+        Ok(Self {
+            rate,
+            burst,
+            max_wait,
+        })
+    }
+}
+
+/// Where definitions of new words come from, selected with `DICTIONARY_PROVIDER`.
+pub(crate) enum ProviderConfig {
+    /// `dictionaryapi`, the default.
+    DictionaryApi(Box<UpstreamConfig>),
+    /// `files`, JSON files in `DICTIONARY_FILES_DIR`.
+    Files { directory: PathBuf },
+}
+
+/// HTTP settings of an upstream dictionary API.
+pub(crate) struct UpstreamConfig {
+    /// Base URL that words are appended to, always ends with `/`.
+    url: Url,
+    timeout: Duration,
+    connect_timeout: Duration,
+    proxy: Option<Url>,
+    user_agent: String,
+    /// Retries of a request after a transient failure.
+    retries: u32,
+    /// Delay before the first retry, doubled for every next one.
+    retry_delay: Duration,
+    /// Consecutive failures that open the circuit breaker.
+    breaker_threshold: u32,
+    /// How long the open circuit breaker rejects requests.
+    breaker_cooldown: Duration,
+}
+
+impl UpstreamConfig {
+    fn new() -> Result<Self> {
+        let url = {
+            let mut input = match std::env::var("UPSTREAM_URL") {
+                Ok(url) => url,
+                Err(_) => "https://api.dictionaryapi.dev/api/v2/entries/en/".to_owned(),
+            };
+            if !input.ends_with('/') {
+                input.push('/');
+            }
+            Url::parse(&input).with_context(|| format!("Invalid UPSTREAM_URL: '{input}'"))?
+        };
+
+        let timeout = Duration::from_secs(env_var_or("UPSTREAM_TIMEOUT_SECS", 10)?);
+        let connect_timeout = Duration::from_secs(env_var_or("UPSTREAM_CONNECT_TIMEOUT_SECS", 5)?);
+
+        let proxy = match std::env::var("UPSTREAM_PROXY") {
+            Ok(proxy) => Some(
+                Url::parse(&proxy).with_context(|| format!("Invalid UPSTREAM_PROXY: '{proxy}'"))?,
+            ),
+            Err(_) => None,
+        };
+
+        let user_agent = match std::env::var("UPSTREAM_USER_AGENT") {
+            Ok(user_agent) => user_agent,
+            Err(_) => "Dictionary webapp.".to_owned(),
+        };
+
+        let retries = env_var_or("UPSTREAM_RETRIES", 2)?;
+        let retry_delay = Duration::from_millis(env_var_or("UPSTREAM_RETRY_DELAY_MS", 200)?);
+        let breaker_threshold = env_var_or("UPSTREAM_BREAKER_THRESHOLD", 5)?.max(1);
+        let breaker_cooldown =
+            Duration::from_secs(env_var_or("UPSTREAM_BREAKER_COOLDOWN_SECS", 30)?);
+
+        Ok(Self {
+            url,
+            timeout,
+            connect_timeout,
+            proxy,
+            user_agent,
+            retries,
+            retry_delay,
+            breaker_threshold,
+            breaker_cooldown,
+        })
+    }
 }
 
 impl Config {
@@ -51,9 +158,43 @@ impl Config {
         };
         let database_url = Url::parse(&input)?;
 
+        let provider = match std::env::var("DICTIONARY_PROVIDER").as_deref() {
+            Ok("dictionaryapi") | Err(_) => {
+                ProviderConfig::DictionaryApi(Box::new(UpstreamConfig::new()?))
+            }
+            Ok("files") => {
+                let directory = std::env::var("DICTIONARY_FILES_DIR")?;
+                ProviderConfig::Files {
+                    directory: PathBuf::from(directory),
+                }
+            }
+            Ok(provider) => bail!("Unknown dictionary provider: '{provider}'"),
+        };
+
+        let rate_limit = RateLimitConfig::new()?;
+
+        let missing_words_ttl = Duration::from_secs(env_var_or("MISSING_WORDS_TTL_SECS", 86400)?);
+
         Ok(Self {
             address,
             database_url,
+            provider,
+            rate_limit,
+            missing_words_ttl,
         })
     }
 }
+
+/// Parses an optional environment variable, falling back to `default` when it is unset.
+fn env_var_or<T>(name: &str, default: T) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .with_context(|| format!("Invalid {name}: '{value}'")),
+        Err(_) => Ok(default),
+    }
+}
@@ -1,10 +1,122 @@
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use unicode_normalization::UnicodeNormalization;
 
 #[derive(Deserialize)]
 pub(crate) struct AddWordForm {
     pub(crate) word: String,
 }
 
+#[derive(Deserialize)]
+pub(crate) struct SearchQuery {
+    pub(crate) q: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct AutocompleteQuery {
+    pub(crate) prefix: Option<String>,
+    pub(crate) limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum WordsSort {
+    #[default]
+    Alphabetical,
+    Recent,
+}
+
+impl WordsSort {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            WordsSort::Alphabetical => "alphabetical",
+            WordsSort::Recent => "recent",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct WordsQuery {
+    #[serde(default)]
+    pub(crate) sort: WordsSort,
+    pub(crate) letter: Option<char>,
+    pub(crate) pos: Option<String>,
+    /// Cursor returned as `next` by the previous page.
+    pub(crate) after: Option<String>,
+    pub(crate) limit: Option<i64>,
+}
+
+/// One page of the word list, `next` is the cursor of the following page.
+#[derive(Debug, Serialize)]
+pub(crate) struct WordsPage {
+    pub(crate) words: Vec<String>,
+    pub(crate) total: i64,
+    pub(crate) next: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RelatedQuery {
+    pub(crate) depth: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Relation {
+    Synonym,
+    Antonym,
+}
+
+/// Words reachable from `word` through synonyms and antonyms.
+#[derive(Debug, Serialize)]
+pub(crate) struct RelatedWords {
+    pub(crate) word: String,
+    pub(crate) depth: i32,
+    pub(crate) nodes: Vec<RelatedNode>,
+    pub(crate) edges: Vec<RelatedEdge>,
+}
+
+/// A word of the graph, `hops` is its distance from the requested word.
+#[derive(Debug, Serialize)]
+pub(crate) struct RelatedNode {
+    pub(crate) word: String,
+    pub(crate) hops: i32,
+    pub(crate) stored: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct RelatedEdge {
+    pub(crate) from: String,
+    pub(crate) to: String,
+    pub(crate) relation: Relation,
+    pub(crate) hops: i32,
+}
+
+/// Word the dictionary provider had no definitions for, not requested again until it expires.
+#[derive(Debug, Serialize)]
+pub(crate) struct MissingWord {
+    pub(crate) word: String,
+    #[serde(rename = "checkedAt")]
+    pub(crate) checked_at: DateTime<Utc>,
+    #[serde(rename = "expiresAt")]
+    pub(crate) expires_at: DateTime<Utc>,
+    pub(crate) expired: bool,
+}
+
+/// How often a part of speech occurs across the dictionary.
+#[derive(Debug, Serialize)]
+pub(crate) struct PartOfSpeechCount {
+    #[serde(rename = "partOfSpeech")]
+    pub(crate) part_of_speech: String,
+    pub(crate) words: i64,
+    pub(crate) meanings: i64,
+}
+
+/// Canonical form of a word used for storage, lookups and cache keys:
+/// trimmed, lowercased and in Unicode NFC.
+pub(crate) fn normalize_word(word: &str) -> String {
+    word.trim().to_lowercase().nfc().collect()
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(untagged)]
 pub(crate) enum ApiResponse {
@@ -17,14 +129,76 @@ pub(crate) struct MissingResponse {
     pub(crate) title: String,
 }
 
+/// A word stored in the dictionary together with its entries.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct StoredWord {
+    pub(crate) word: String,
+    #[serde(rename = "fetchedAt")]
+    pub(crate) fetched_at: DateTime<Utc>,
+    pub(crate) entries: Vec<WordEntry>,
+}
+
+/// Definition matching a full-text search query.
+#[derive(Debug, Serialize)]
+pub(crate) struct SearchHit {
+    pub(crate) word: String,
+    pub(crate) headword: String,
+    #[serde(rename = "partOfSpeech")]
+    pub(crate) part_of_speech: String,
+    pub(crate) definition: Vec<Highlight>,
+    pub(crate) example: Option<Vec<Highlight>>,
+    pub(crate) rank: f32,
+}
+
+/// Word whose definitions or synonyms match a reverse lookup.
+#[derive(Debug, Serialize)]
+pub(crate) struct ReverseHit {
+    pub(crate) word: String,
+    /// Best matching definition, missing when only a synonym matched.
+    pub(crate) definition: Option<String>,
+    /// Whether the word lists the looked up term as a synonym.
+    pub(crate) synonym: bool,
+    pub(crate) rank: f32,
+}
+
+/// Part of a search snippet, `highlighted` parts match the query.
+#[derive(Debug, Serialize)]
+pub(crate) struct Highlight {
+    pub(crate) text: String,
+    pub(crate) highlighted: bool,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub(crate) struct WordEntry {
     pub(crate) word: String,
+    #[serde(default)]
+    pub(crate) phonetic: Option<String>,
+    #[serde(default)]
+    pub(crate) phonetics: Vec<Phonetic>,
+    #[serde(default)]
+    pub(crate) origin: Option<String>,
     pub(crate) meanings: Vec<Meaning>,
+    #[serde(default)]
+    pub(crate) license: Option<License>,
     #[serde(rename = "sourceUrls")]
     pub(crate) source_urls: Vec<String>,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct License {
+    pub(crate) name: String,
+    pub(crate) url: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct Phonetic {
+    #[serde(default)]
+    pub(crate) text: Option<String>,
+    /// Link to a pronunciation recording, upstream sends an empty string when there is none.
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub(crate) audio: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub(crate) struct Meaning {
     #[serde(rename = "partOfSpeech")]
@@ -38,4 +212,17 @@ pub(crate) struct Meaning {
 pub(crate) struct Definition {
     pub(crate) definition: String,
     pub(crate) example: Option<String>,
+    #[serde(default)]
+    pub(crate) synonyms: Vec<String>,
+    #[serde(default)]
+    pub(crate) antonyms: Vec<String>,
+}
+
+fn empty_as_none<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+
+    Ok(value.filter(|value| !value.is_empty()))
 }
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use log::info;
+use reqwest::{header::RETRY_AFTER, StatusCode};
+use url::Url;
+
+use super::{DictionaryProvider, UpstreamError};
+use crate::{
+    model::{ApiResponse, WordEntry},
+    UpstreamConfig,
+};
+
+/// Client of the Free Dictionary API, <https://dictionaryapi.dev>.
+pub(crate) struct DictionaryApiProvider {
+    client: reqwest::Client,
+    dictionary_api: Url,
+}
+
+impl DictionaryApiProvider {
+    pub(crate) fn new(config: &UpstreamConfig) -> Result<Self> {
+        info!("Dictionary API url: {}", config.url);
+
+        let mut builder = reqwest::Client::builder()
+            .user_agent(&config.user_agent)
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout);
+
+        if let Some(proxy) = &config.proxy {
+            info!("Dictionary API proxy: {proxy}");
+            builder = builder.proxy(reqwest::Proxy::all(proxy.clone())?);
+        }
+
+        let client = builder
+            .build()
+            .context("Cannot build Dictionary API client")?;
+
+        Ok(Self {
+            client,
+            dictionary_api: config.url.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl DictionaryProvider for DictionaryApiProvider {
+    fn name(&self) -> &'static str {
+        "Dictionary API"
+    }
+
+    async fn request_word_definitions(&self, word: &str) -> Result<Option<Vec<WordEntry>>> {
+        info!("Request definition for word: '{word}'");
+        let word_url = self.dictionary_api.join(word)?;
+        info!("API url: '{word_url}'");
+        let request = self.client.get(word_url);
+
+        info!("Sending request...");
+        let response = request.send().await.map_err(UpstreamError::from)?;
+
+        let status = response.status();
+        info!("Response received for word: '{word}', status: {status}");
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+                .map(Duration::from_secs);
+
+            return Err(UpstreamError::RateLimited { retry_after }.into());
+        }
+        // Unknown words are answered with 404 and a JSON body.
+        if !status.is_success() && status != StatusCode::NOT_FOUND {
+            return Err(UpstreamError::Unavailable(format!("status {status}")).into());
+        }
+
+        let body = response.bytes().await.map_err(UpstreamError::from)?;
+        let response = serde_json::from_slice::<ApiResponse>(&body)
+            .map_err(|err| UpstreamError::MalformedResponse(err.to_string()))?;
+
+        let word_definitions = match response {
+            ApiResponse::Success(word_entries) => Some(word_entries),
+            ApiResponse::Missing(_) => None,
+        };
+
+        Ok(word_definitions)
+    }
+}
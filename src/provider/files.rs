@@ -0,0 +1,51 @@
+use std::{io::ErrorKind, path::PathBuf};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use log::info;
+
+use super::DictionaryProvider;
+use crate::model::WordEntry;
+
+/// Reads entries from `<directory>/<word>.json` files in the Dictionary API format.
+///
+/// Suits glossaries and converted dumps, a missing file means the word is unknown.
+pub(crate) struct FilesProvider {
+    directory: PathBuf,
+}
+
+impl FilesProvider {
+    pub(crate) fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+}
+
+#[async_trait]
+impl DictionaryProvider for FilesProvider {
+    fn name(&self) -> &'static str {
+        "files"
+    }
+
+    async fn request_word_definitions(&self, word: &str) -> Result<Option<Vec<WordEntry>>> {
+        // Words come from URLs and forms, keep them inside the directory.
+        if word.contains(['/', '\\']) || word.starts_with('.') {
+            return Ok(None);
+        }
+
+        let path = self.directory.join(format!("{word}.json"));
+        info!("Reading definitions for word from: '{}'", path.display());
+
+        let contents = match tokio::fs::read(&path).await {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Cannot read '{}'", path.display()))
+            }
+        };
+
+        let word_entries = serde_json::from_slice(&contents)
+            .with_context(|| format!("Invalid definitions in '{}'", path.display()))?;
+
+        Ok(Some(word_entries))
+    }
+}
@@ -0,0 +1,99 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::{model::WordEntry, ProviderConfig, RateLimitConfig};
+
+mod dictionary_api;
+mod files;
+mod rate_limiter;
+mod resilient;
+
+pub(crate) use dictionary_api::DictionaryApiProvider;
+pub(crate) use files::FilesProvider;
+pub(crate) use rate_limiter::RateLimitedProvider;
+pub(crate) use resilient::ResilientProvider;
+
+/// Failure of an upstream dictionary service, mapped to a dedicated response.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum UpstreamError {
+    #[error("Upstream is unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("Upstream rate limited the request")]
+    RateLimited { retry_after: Option<Duration> },
+
+    #[error("Upstream returned a malformed response: {0}")]
+    MalformedResponse(String),
+
+    #[error("Upstream did not respond in time")]
+    Timeout,
+
+    #[error("Outbound rate limit exceeded, next request possible in {retry_after:?}")]
+    Throttled { retry_after: Duration },
+}
+
+impl From<reqwest::Error> for UpstreamError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            UpstreamError::Timeout
+        } else if err.is_decode() {
+            UpstreamError::MalformedResponse(err.to_string())
+        } else {
+            UpstreamError::Unavailable(err.to_string())
+        }
+    }
+}
+
+/// Source of definitions requested when a word is added or refreshed.
+#[async_trait]
+pub(crate) trait DictionaryProvider: Send + Sync {
+    /// Name of the provider used in logs.
+    fn name(&self) -> &'static str;
+
+    /// Entries for a normalized word, `None` if the provider has no definitions for it.
+    async fn request_word_definitions(&self, word: &str) -> Result<Option<Vec<WordEntry>>>;
+
+    /// Circuit breaker state and request counters, if the provider keeps them.
+    fn metrics(&self) -> Option<UpstreamMetrics> {
+        None
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct UpstreamMetrics {
+    /// `closed`, `open` or `half_open`.
+    pub(crate) state: &'static str,
+    #[serde(rename = "consecutiveFailures")]
+    pub(crate) consecutive_failures: u32,
+    pub(crate) requests: u64,
+    pub(crate) failures: u64,
+    pub(crate) retries: u64,
+    #[serde(rename = "shortCircuited")]
+    pub(crate) short_circuited: u64,
+    pub(crate) opened: u64,
+}
+
+/// Builds the configured provider, every request it sends takes a token from
+/// the rate limit, including retries. The circuit breaker is checked first so
+/// short-circuited requests don't use up the budget.
+pub(crate) fn initialize(
+    config: ProviderConfig,
+    rate_limit: &RateLimitConfig,
+) -> Result<Arc<dyn DictionaryProvider>> {
+    let provider: Arc<dyn DictionaryProvider> = match config {
+        ProviderConfig::DictionaryApi(config) => {
+            let provider = DictionaryApiProvider::new(&config)?;
+            let provider = RateLimitedProvider::new(Box::new(provider), rate_limit);
+            Arc::new(ResilientProvider::new(Box::new(provider), &config))
+        }
+        ProviderConfig::Files { directory } => Arc::new(RateLimitedProvider::new(
+            Box::new(FilesProvider::new(directory)),
+            rate_limit,
+        )),
+    };
+
+    Ok(provider)
+}
@@ -0,0 +1,155 @@
+use std::{sync::Mutex, time::Duration};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::time::Instant;
+
+use super::{DictionaryProvider, UpstreamError, UpstreamMetrics};
+use crate::{model::WordEntry, RateLimitConfig};
+
+/// Takes a token from the shared bucket for every request to the inner provider,
+/// so retries of a decorating provider are limited as well.
+pub(crate) struct RateLimitedProvider {
+    inner: Box<dyn DictionaryProvider>,
+    limiter: RateLimiter,
+}
+
+impl RateLimitedProvider {
+    pub(crate) fn new(inner: Box<dyn DictionaryProvider>, config: &RateLimitConfig) -> Self {
+        Self {
+            inner,
+            limiter: RateLimiter::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl DictionaryProvider for RateLimitedProvider {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn request_word_definitions(&self, word: &str) -> Result<Option<Vec<WordEntry>>> {
+        self.limiter.acquire().await?;
+
+        self.inner.request_word_definitions(word).await
+    }
+
+    fn metrics(&self) -> Option<UpstreamMetrics> {
+        self.inner.metrics()
+    }
+}
+
+/// Token bucket limiting requests to the dictionary provider.
+///
+/// Requests over the rate reserve a future token and wait for it in order,
+/// unless the wait would exceed `max_wait`.
+struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    max_wait: Duration,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    /// Negative when tokens are reserved by waiting requests.
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    fn new(config: &RateLimitConfig) -> Self {
+        let burst = f64::from(config.burst);
+
+        Self {
+            rate: config.rate,
+            burst,
+            max_wait: config.max_wait,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token, waiting for it if the bucket is empty.
+    async fn acquire(&self) -> Result<(), UpstreamError> {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+            bucket.updated = now;
+
+            let wait = if bucket.tokens >= 1.0 {
+                Duration::ZERO
+            } else {
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate)
+            };
+
+            if wait > self.max_wait {
+                return Err(UpstreamError::Throttled { retry_after: wait });
+            }
+
+            bucket.tokens -= 1.0;
+            wait
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(rate: f64, burst: u32, max_wait: Duration) -> RateLimiter {
+        RateLimiter::new(&RateLimitConfig {
+            rate,
+            burst,
+            max_wait,
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn allows_burst_then_throttles() {
+        let limiter = limiter(2.0, 3, Duration::ZERO);
+
+        for _ in 0..3 {
+            assert!(limiter.acquire().await.is_ok());
+        }
+
+        let Err(UpstreamError::Throttled { retry_after }) = limiter.acquire().await else {
+            panic!("request over the burst was not throttled");
+        };
+        assert_eq!(retry_after, Duration::from_millis(500));
+
+        tokio::time::advance(Duration::from_millis(500)).await;
+        assert!(limiter.acquire().await.is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_reserved_tokens_up_to_max_wait() {
+        let limiter = limiter(1.0, 1, Duration::from_secs(2));
+        let started = Instant::now();
+
+        let (first, second, third, fourth) = tokio::join!(
+            limiter.acquire(),
+            limiter.acquire(),
+            limiter.acquire(),
+            limiter.acquire(),
+        );
+
+        assert!(first.is_ok() && second.is_ok() && third.is_ok());
+        assert!(matches!(
+            fourth,
+            Err(UpstreamError::Throttled { retry_after }) if retry_after == Duration::from_secs(3)
+        ));
+        assert_eq!(started.elapsed(), Duration::from_secs(2));
+    }
+}
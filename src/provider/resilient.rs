@@ -0,0 +1,349 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use log::{info, warn};
+use tokio::time::Instant;
+
+use super::{DictionaryProvider, UpstreamError, UpstreamMetrics};
+use crate::{model::WordEntry, UpstreamConfig};
+
+/// Retries transient failures of the inner provider with jittered exponential
+/// backoff and stops calling it through a circuit breaker while it keeps failing.
+pub(crate) struct ResilientProvider {
+    inner: Box<dyn DictionaryProvider>,
+    retries: u32,
+    retry_delay: Duration,
+    breaker_threshold: u32,
+    breaker_cooldown: Duration,
+    breaker: Mutex<BreakerState>,
+    requests: AtomicU64,
+    failures: AtomicU64,
+    retried: AtomicU64,
+    short_circuited: AtomicU64,
+    opened: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BreakerState {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// One trial request is let through, a new one is allowed after `until`
+    /// in case the trial never finished.
+    HalfOpen {
+        until: Instant,
+    },
+}
+
+impl BreakerState {
+    fn name(self) -> &'static str {
+        match self {
+            BreakerState::Closed { .. } => "closed",
+            BreakerState::Open { .. } => "open",
+            BreakerState::HalfOpen { .. } => "half_open",
+        }
+    }
+}
+
+/// What a request outcome means for retries and the circuit breaker.
+enum Outcome {
+    Success,
+    /// Failure that may pass when the request is repeated.
+    Transient,
+    Failure,
+    /// Not caused by the upstream, e.g. an invalid word URL.
+    Ignored,
+}
+
+impl ResilientProvider {
+    pub(crate) fn new(inner: Box<dyn DictionaryProvider>, config: &UpstreamConfig) -> Self {
+        Self {
+            inner,
+            retries: config.retries,
+            retry_delay: config.retry_delay,
+            breaker_threshold: config.breaker_threshold,
+            breaker_cooldown: config.breaker_cooldown,
+            breaker: Mutex::new(BreakerState::Closed { failures: 0 }),
+            requests: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            retried: AtomicU64::new(0),
+            short_circuited: AtomicU64::new(0),
+            opened: AtomicU64::new(0),
+        }
+    }
+
+    fn acquire(&self) -> Result<(), UpstreamError> {
+        let mut breaker = self.breaker.lock().unwrap();
+
+        match *breaker {
+            BreakerState::Closed { .. } => Ok(()),
+            BreakerState::Open { until } | BreakerState::HalfOpen { until }
+                if Instant::now() >= until =>
+            {
+                info!(
+                    "Circuit breaker of {} is half-open, sending a trial request",
+                    self.inner.name()
+                );
+                *breaker = BreakerState::HalfOpen {
+                    until: Instant::now() + self.breaker_cooldown,
+                };
+                Ok(())
+            }
+            BreakerState::Open { .. } | BreakerState::HalfOpen { .. } => {
+                self.short_circuited.fetch_add(1, Ordering::Relaxed);
+                Err(UpstreamError::Unavailable(format!(
+                    "circuit breaker of {} is open",
+                    self.inner.name()
+                )))
+            }
+        }
+    }
+
+    fn record_success(&self) {
+        let mut breaker = self.breaker.lock().unwrap();
+
+        if !matches!(*breaker, BreakerState::Closed { .. }) {
+            info!("Circuit breaker of {} is closed", self.inner.name());
+        }
+        *breaker = BreakerState::Closed { failures: 0 };
+    }
+
+    fn record_failure(&self) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+        let mut breaker = self.breaker.lock().unwrap();
+
+        let failures = match *breaker {
+            BreakerState::Closed { failures } => failures + 1,
+            BreakerState::Open { .. } | BreakerState::HalfOpen { .. } => self.breaker_threshold,
+        };
+
+        *breaker = if failures >= self.breaker_threshold {
+            warn!(
+                "Circuit breaker of {} is open for {:?} after {failures} failures",
+                self.inner.name(),
+                self.breaker_cooldown
+            );
+            self.opened.fetch_add(1, Ordering::Relaxed);
+            BreakerState::Open {
+                until: Instant::now() + self.breaker_cooldown,
+            }
+        } else {
+            BreakerState::Closed { failures }
+        };
+    }
+
+    /// Exponential backoff with jitter, between half and the whole delay.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .retry_delay
+            .saturating_mul(2_u32.saturating_pow(attempt));
+        delay / 2 + (delay / 2).mul_f64(fastrand::f64())
+    }
+}
+
+fn outcome<T>(result: &Result<T>) -> Outcome {
+    let Err(err) = result else {
+        return Outcome::Success;
+    };
+
+    match err.downcast_ref::<UpstreamError>() {
+        Some(UpstreamError::Unavailable(_) | UpstreamError::Timeout) => Outcome::Transient,
+        Some(UpstreamError::MalformedResponse(_)) => Outcome::Failure,
+        // The upstream answered, it is only busy.
+        Some(UpstreamError::RateLimited { .. }) => Outcome::Success,
+        Some(UpstreamError::Throttled { .. }) | None => Outcome::Ignored,
+    }
+}
+
+#[async_trait]
+impl DictionaryProvider for ResilientProvider {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn request_word_definitions(&self, word: &str) -> Result<Option<Vec<WordEntry>>> {
+        let mut attempt = 0;
+
+        loop {
+            self.acquire()?;
+            self.requests.fetch_add(1, Ordering::Relaxed);
+
+            let result = self.inner.request_word_definitions(word).await;
+
+            match outcome(&result) {
+                Outcome::Success => self.record_success(),
+                Outcome::Transient => {
+                    self.record_failure();
+
+                    let closed =
+                        matches!(*self.breaker.lock().unwrap(), BreakerState::Closed { .. });
+                    if closed && attempt < self.retries {
+                        let delay = self.backoff(attempt);
+                        attempt += 1;
+                        self.retried.fetch_add(1, Ordering::Relaxed);
+                        warn!(
+                            "Request to {} for word '{word}' failed, retry {attempt} in {delay:?}",
+                            self.inner.name()
+                        );
+                        tokio::time::sleep(delay).await;
+                        continue;
+                    }
+                }
+                Outcome::Failure => self.record_failure(),
+                Outcome::Ignored => {}
+            }
+
+            return result;
+        }
+    }
+
+    fn metrics(&self) -> Option<UpstreamMetrics> {
+        let (state, consecutive_failures) = match *self.breaker.lock().unwrap() {
+            state @ BreakerState::Closed { failures } => (state.name(), failures),
+            state => (state.name(), self.breaker_threshold),
+        };
+
+        Some(UpstreamMetrics {
+            state,
+            consecutive_failures,
+            requests: self.requests.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            retries: self.retried.load(Ordering::Relaxed),
+            short_circuited: self.short_circuited.load(Ordering::Relaxed),
+            opened: self.opened.load(Ordering::Relaxed),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use url::Url;
+
+    use super::*;
+
+    /// Answers with queued results, then with an empty entry list.
+    struct Scripted {
+        results: Mutex<VecDeque<Result<Option<Vec<WordEntry>>>>>,
+        delay: Duration,
+        calls: AtomicUsize,
+    }
+
+    impl Scripted {
+        fn failing(failures: usize) -> Self {
+            Self {
+                results: Mutex::new(
+                    (0..failures)
+                        .map(|_| Err(UpstreamError::Unavailable("500".to_owned()).into()))
+                        .collect(),
+                ),
+                delay: Duration::from_secs(1),
+                calls: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl DictionaryProvider for Arc<Scripted> {
+        fn name(&self) -> &'static str {
+            "scripted"
+        }
+
+        async fn request_word_definitions(&self, _word: &str) -> Result<Option<Vec<WordEntry>>> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(self.delay).await;
+
+            let result = self.results.lock().unwrap().pop_front();
+            result.unwrap_or(Ok(Some(Vec::new())))
+        }
+    }
+
+    fn resilient(inner: &Arc<Scripted>, retries: u32) -> ResilientProvider {
+        let config = UpstreamConfig {
+            url: Url::parse("http://localhost/").unwrap(),
+            timeout: Duration::from_secs(10),
+            connect_timeout: Duration::from_secs(5),
+            proxy: None,
+            user_agent: "test".to_owned(),
+            retries,
+            retry_delay: Duration::from_millis(200),
+            breaker_threshold: 3,
+            breaker_cooldown: Duration::from_secs(30),
+        };
+
+        ResilientProvider::new(Box::new(Arc::clone(inner)), &config)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_transient_failures() {
+        let inner = Arc::new(Scripted::failing(2));
+        let provider = resilient(&inner, 2);
+
+        let result = provider.request_word_definitions("word").await;
+
+        assert!(result.is_ok());
+        assert_eq!(inner.calls.load(Ordering::Relaxed), 3);
+        assert_eq!(provider.metrics().unwrap().retries, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn breaker_opens_after_threshold_failures() {
+        let inner = Arc::new(Scripted::failing(3));
+        let provider = resilient(&inner, 0);
+
+        for _ in 0..3 {
+            assert!(provider.request_word_definitions("word").await.is_err());
+        }
+        assert_eq!(provider.metrics().unwrap().state, "open");
+
+        let err = provider.request_word_definitions("word").await.unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<UpstreamError>(),
+            Some(UpstreamError::Unavailable(_))
+        ));
+        assert_eq!(inner.calls.load(Ordering::Relaxed), 3);
+        assert_eq!(provider.metrics().unwrap().short_circuited, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn half_open_breaker_lets_one_trial_through_and_closes_on_success() {
+        let inner = Arc::new(Scripted::failing(3));
+        let provider = resilient(&inner, 0);
+
+        for _ in 0..3 {
+            assert!(provider.request_word_definitions("word").await.is_err());
+        }
+        tokio::time::advance(Duration::from_secs(30)).await;
+
+        let (trial, other) = tokio::join!(
+            provider.request_word_definitions("word"),
+            provider.request_word_definitions("word"),
+        );
+
+        assert!(trial.is_ok());
+        assert!(other.is_err());
+        assert_eq!(inner.calls.load(Ordering::Relaxed), 4);
+
+        let metrics = provider.metrics().unwrap();
+        assert_eq!(metrics.state, "closed");
+        assert_eq!(metrics.consecutive_failures, 0);
+    }
+}
@@ -1,41 +1,117 @@
-use std::sync::{Arc, Mutex};
-
-use crate::model::{ApiResponse, Definition, Meaning, WordEntry};
-use anyhow::Result;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use crate::{
+    model::{
+        normalize_word, Definition, Highlight, License, Meaning, MissingWord, PartOfSpeechCount,
+        Phonetic, RelatedEdge, Relation, ReverseHit, SearchHit, StoredWord, WordEntry,
+    },
+    provider::{DictionaryProvider, UpstreamMetrics},
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use log::info;
 use lru::LruCache;
-use sqlx::{postgres::PgConnectOptions, ConnectOptions, PgPool, Pool, Postgres};
+use single_flight::SingleFlight;
+use sqlx::{postgres::PgConnectOptions, ConnectOptions, PgPool, Pool, Postgres, Transaction};
 use url::Url;
 
+mod single_flight;
+
 #[derive(Clone)]
 pub(crate) struct Repository {
     pool: Pool<Postgres>,
-    client: reqwest::Client,
-    dictionary_api: Url,
-    words_cache: Arc<Mutex<LruCache<String, Vec<WordEntry>>>>,
+    provider: Arc<dyn DictionaryProvider>,
+    word_loads: Arc<SingleFlight<Option<StoredWord>>>,
+    upstream_fetches: Arc<SingleFlight<Option<Vec<WordEntry>>>>,
+    missing_words_ttl: Duration,
+    words_cache: Arc<Mutex<LruCache<String, StoredWord>>>,
+    /// Bumped under the cache lock whenever a word is changed or deleted, so loads
+    /// and adds that raced the change don't cache what they read.
+    words_generation: Arc<AtomicU64>,
 }
 
 struct DbWord {
     word: String,
 }
 
+struct DbSearchHit {
+    word: String,
+    headword: String,
+    part_of_speech: String,
+    definition: String,
+    example: Option<String>,
+    rank: f32,
+}
+
+/// Markers around matches in `ts_headline` output, they can't appear in stored text.
+const HIGHLIGHT_START: char = '\u{2}';
+const HIGHLIGHT_STOP: char = '\u{3}';
+
+/// Reverse lookup rank for listing the term as a synonym, well above typical
+/// definition ranks.
+const SYNONYM_RANK: f32 = 1.0;
+
+struct DbMissingWord {
+    word: String,
+    checked_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    expired: bool,
+}
+
+struct DbReverseHit {
+    word: String,
+    definition: Option<String>,
+    synonym: bool,
+    rank: f32,
+}
+
 struct DbWordEntry {
     id: i32,
+    word_id: i32,
     word: String,
+    phonetic: Option<String>,
+    origin: Option<String>,
+    license_name: Option<String>,
+    license_url: Option<String>,
 }
 
 struct DbSourceUrl {
-    id: i32,
     word_entry_id: i32,
     url: String,
 }
 
+struct DbPhonetic {
+    word_entry_id: i32,
+    text: Option<String>,
+    audio: Option<String>,
+}
+
 struct DbMeaning {
     id: i32,
     word_entry_id: i32,
     part_of_speech: String,
 }
 
+struct DbRelatedEdge {
+    word: String,
+    related: String,
+    antonym: bool,
+    hops: i32,
+}
+
+struct DbPartOfSpeechCount {
+    part_of_speech: String,
+    words: i64,
+    meanings: i64,
+}
+
 struct DbDefinition {
     id: i32,
     meaning_id: i32,
@@ -44,19 +120,31 @@ struct DbDefinition {
 }
 
 struct DbSynonym {
-    id: i32,
     meaning_id: i32,
     synonym: String,
 }
 
 struct DbAntonym {
-    id: i32,
     meaning_id: i32,
     antonym: String,
 }
 
+struct DbDefinitionSynonym {
+    definition_id: i32,
+    synonym: String,
+}
+
+struct DbDefinitionAntonym {
+    definition_id: i32,
+    antonym: String,
+}
+
 impl Repository {
-    pub(crate) async fn initialize(database_url: Url) -> Result<Self> {
+    pub(crate) async fn initialize(
+        database_url: Url,
+        provider: Arc<dyn DictionaryProvider>,
+        missing_words_ttl: Duration,
+    ) -> Result<Self> {
         info!("Initializing repository with url: {database_url}");
         let options = PgConnectOptions::from_url(&database_url)?;
 
@@ -67,134 +155,194 @@ impl Repository {
         sqlx::migrate!().run(&pool).await?;
         info!("Migrations applied successfully");
 
-        let client = reqwest::Client::builder()
-            .user_agent("Dictionary webapp.")
-            .build()
-            .unwrap();
-
-        let dictionary_api =
-            Url::parse("https://api.dictionaryapi.dev/api/v2/entries/en/").unwrap();
-
         let words_cache = Arc::new(Mutex::new(LruCache::new(
             std::num::NonZero::new(100).unwrap(),
         )));
 
         Ok(Self {
             pool,
-            client,
-            dictionary_api,
+            provider,
+            word_loads: Arc::new(SingleFlight::new()),
+            upstream_fetches: Arc::new(SingleFlight::new()),
+            missing_words_ttl,
             words_cache,
+            words_generation: Arc::new(AtomicU64::new(0)),
         })
     }
 
-    pub(crate) async fn request_word_definitions(&self, word: &str) -> Result<ApiResponse> {
-        info!("Request definition for word: '{word}'");
-        let word_url = self.dictionary_api.join(word)?;
-        info!("API url: '{word_url}'");
-        let request = self.client.get(word_url);
+    /// Requests definitions of a word from the configured provider.
+    ///
+    /// Concurrent requests for one word share a single upstream request.
+    pub(crate) async fn request_word_definitions(
+        &self,
+        word: &str,
+    ) -> Result<Option<Vec<WordEntry>>> {
+        self.upstream_fetches
+            .run(word, || self.provider.request_word_definitions(word))
+            .await
+    }
+
+    pub(crate) fn provider_name(&self) -> &'static str {
+        self.provider.name()
+    }
 
-        info!("Sending request...");
-        let response = request.send().await?;
+    pub(crate) fn provider_metrics(&self) -> Option<UpstreamMetrics> {
+        self.provider.metrics()
+    }
 
-        info!("Response received for word: '{word}'");
-        let word_definitions = response.json::<ApiResponse>().await?;
+    pub(crate) async fn word_exists(&self, word: &str) -> Result<bool> {
+        let exists = sqlx::query!(
+            r#"
+            select exists (select 1 from words where lower(word) = lower($1)) as "exists!"
+            "#,
+            word
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .exists;
 
-        Ok(word_definitions)
+        Ok(exists)
     }
 
+    /// Stores entries for a new word, returns `false` if the word is already stored.
     pub(crate) async fn add_word_entries(
         &self,
         word: &str,
         word_entries: Vec<WordEntry>,
-    ) -> Result<()> {
+    ) -> Result<bool> {
+        let generation = self.words_generation.load(Ordering::SeqCst);
         let mut transaction = self.pool.begin().await?;
 
-        for word_entry in &word_entries {
-            let word_entry_id: i32 = sqlx::query!(
-                r#"
-                insert into word_entries (word)
-                values ($1)
-                returning id
-                "#,
-                word_entry.word
-            )
-            .fetch_one(&mut *transaction)
-            .await?
-            .id;
-
-            for url in &word_entry.source_urls {
-                sqlx::query!(
-                    r#"
-                    insert into source_urls (word_entry_id, url)
-                    values ($1, $2)
-                    "#,
-                    word_entry_id,
-                    url
-                )
-                .execute(&mut *transaction)
-                .await?;
-            }
+        let inserted = sqlx::query!(
+            r#"
+            insert into words (word)
+            values ($1)
+            on conflict ((lower(word))) do nothing
+            returning id, fetched_at
+            "#,
+            word
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
 
-            for meaning in &word_entry.meanings {
-                let meaning_id: i32 = sqlx::query!(
-                    r#"
-                    insert into meanings (word_entry_id, part_of_speech)
-                    values ($1, $2)
-                    returning id
-                    "#,
-                    word_entry_id,
-                    meaning.part_of_speech
-                )
-                .fetch_one(&mut *transaction)
-                .await?
-                .id;
-
-                for definition in &meaning.definitions {
-                    sqlx::query!(
-                        r#"
-                        insert into definitions (meaning_id, definition, example)
-                        values ($1, $2, $3)
-                        "#,
-                        meaning_id,
-                        definition.definition,
-                        definition.example
-                    )
-                    .execute(&mut *transaction)
-                    .await?;
-                }
+        let Some(inserted) = inserted else {
+            return Ok(false);
+        };
 
-                for synonym in &meaning.synonyms {
-                    sqlx::query!(
-                        r#"
-                        insert into synonyms (meaning_id, synonym)
-                        values ($1, $2)
-                        "#,
-                        meaning_id,
-                        synonym
-                    )
-                    .execute(&mut *transaction)
-                    .await?;
-                }
+        insert_word_entries(&mut transaction, inserted.id, &word_entries).await?;
 
-                for antonym in &meaning.antonyms {
-                    sqlx::query!(
-                        r#"
-                        insert into antonyms (meaning_id, antonym)
-                        values ($1, $2)
-                        "#,
-                        meaning_id,
-                        antonym
-                    )
-                    .execute(&mut *transaction)
-                    .await?;
-                }
+        transaction.commit().await?;
+
+        {
+            let mut cache_guard = self.words_cache.lock().unwrap();
+
+            // A delete may have committed since, leave the word to the next load.
+            if self.words_generation.load(Ordering::SeqCst) == generation {
+                let stored_word = StoredWord {
+                    word: word.to_owned(),
+                    fetched_at: inserted.fetched_at,
+                    entries: word_entries,
+                };
+                cache_guard.put(word.to_owned(), stored_word);
             }
         }
 
+        Ok(true)
+    }
+
+    /// Re-requests definitions of a stored word and replaces its entry tree.
+    ///
+    /// Returns `None` if the word is not stored and `Some(false)` if the provider
+    /// no longer knows the word, stored entries are kept then.
+    pub(crate) async fn refresh_word(&self, word: &str) -> Result<Option<bool>> {
+        if !self.word_exists(word).await? {
+            return Ok(None);
+        }
+
+        let word_entries = self.request_word_definitions(word).await?;
+        let Some(word_entries) = word_entries.filter(|entries| !entries.is_empty()) else {
+            return Ok(Some(false));
+        };
+
+        let mut transaction = self.pool.begin().await?;
+
+        let updated = sqlx::query!(
+            r#"
+            update words
+            set fetched_at = now()
+            where lower(word) = lower($1)
+            returning id
+            "#,
+            word
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        let Some(updated) = updated else {
+            return Ok(None);
+        };
+
         sqlx::query!(
             r#"
-            insert into words (word)
+            delete from word_entries
+            where word_id = $1
+            "#,
+            updated.id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        insert_word_entries(&mut transaction, updated.id, &word_entries).await?;
+
+        transaction.commit().await?;
+
+        self.invalidate_word(word);
+
+        Ok(Some(true))
+    }
+
+    /// Whether the provider had no definitions for the word within the negative cache TTL.
+    pub(crate) async fn is_word_missing(&self, word: &str) -> Result<bool> {
+        let missing = sqlx::query!(
+            r#"
+            select exists (
+                select 1
+                from missing_words
+                where word = $1 and checked_at > now() - make_interval(secs => $2)
+            ) as "missing!"
+            "#,
+            word,
+            self.missing_words_ttl.as_secs_f64()
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .missing;
+
+        Ok(missing)
+    }
+
+    /// Remembers that the provider has no definitions for the word.
+    ///
+    /// Expired entries are dropped here, the table only grows on this path.
+    pub(crate) async fn add_missing_word(&self, word: &str) -> Result<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            delete from missing_words
+            where checked_at + make_interval(secs => $1) <= now()
+            "#,
+            self.missing_words_ttl.as_secs_f64()
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            insert into missing_words (word)
             values ($1)
+            on conflict (word) do update
+            set checked_at = now()
             "#,
             word
         )
@@ -203,35 +351,161 @@ impl Repository {
 
         transaction.commit().await?;
 
-        {
-            let mut cache_guard = self.words_cache.lock().unwrap();
+        Ok(())
+    }
+
+    /// Negative cache entries, most recently checked first.
+    pub(crate) async fn get_missing_words(&self) -> Result<Vec<MissingWord>> {
+        let missing_words = sqlx::query_as!(
+            DbMissingWord,
+            r#"
+            select
+                word,
+                checked_at,
+                checked_at + make_interval(secs => $1) as "expires_at!",
+                checked_at + make_interval(secs => $1) <= now() as "expired!"
+            from missing_words
+            order by checked_at desc, word
+            "#,
+            self.missing_words_ttl.as_secs_f64()
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(
+            |DbMissingWord {
+                 word,
+                 checked_at,
+                 expires_at,
+                 expired,
+             }| MissingWord {
+                word,
+                checked_at,
+                expires_at,
+                expired,
+            },
+        )
+        .collect();
 
-            cache_guard.put(word.to_owned(), word_entries);
+        Ok(missing_words)
+    }
+
+    /// Forgets a negative cache entry, returns `false` if there was none.
+    pub(crate) async fn delete_missing_word(&self, word: &str) -> Result<bool> {
+        let deleted = sqlx::query!(
+            r#"
+            delete from missing_words
+            where word = $1
+            "#,
+            word
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(deleted > 0)
+    }
+
+    /// Clears the negative cache, returns the number of removed entries.
+    pub(crate) async fn clear_missing_words(&self) -> Result<u64> {
+        let deleted = sqlx::query!(
+            r#"
+            delete from missing_words
+            "#
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(deleted)
+    }
+
+    /// Removes a word with its entry tree, returns `false` if nothing was stored.
+    pub(crate) async fn delete_word(&self, word: &str) -> Result<bool> {
+        let deleted = sqlx::query!(
+            r#"
+            delete from words
+            where lower(word) = lower($1)
+            "#,
+            word
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if deleted > 0 {
+            self.invalidate_word(word);
         }
 
-        Ok(())
+        Ok(deleted > 0)
     }
 
-    #[allow(clippy::too_many_lines)]
-    pub(crate) async fn get_word_definitions(&self, word: &str) -> Result<Option<Vec<WordEntry>>> {
+    /// Returns a cached word or loads it, concurrent loads of one word share a single load.
+    pub(crate) async fn get_word_definitions(&self, word: &str) -> Result<Option<StoredWord>> {
         {
             let mut cache_guard = self.words_cache.lock().unwrap();
 
-            if let Some(word_entries) = cache_guard.get(word) {
-                return Ok(Some(word_entries.clone()));
+            if let Some(stored_word) = cache_guard.get(word) {
+                return Ok(Some(stored_word.clone()));
             }
         }
 
+        self.word_loads
+            .run(word, || async {
+                let generation = self.words_generation.load(Ordering::SeqCst);
+                let stored_word = self.load_word_definitions(word).await?;
+
+                let mut cache_guard = self.words_cache.lock().unwrap();
+
+                // A word changed while this one was loading, what was read may be stale.
+                if let Some(stored_word) = &stored_word {
+                    if self.words_generation.load(Ordering::SeqCst) == generation {
+                        cache_guard.put(word.to_owned(), stored_word.clone());
+                    }
+                }
+
+                Ok(stored_word)
+            })
+            .await
+    }
+
+    /// Drops a changed or deleted word from the cache, loads still in flight
+    /// don't cache it and later callers don't join them.
+    fn invalidate_word(&self, word: &str) {
+        let mut cache_guard = self.words_cache.lock().unwrap();
+
+        self.words_generation.fetch_add(1, Ordering::SeqCst);
+        cache_guard.pop(word);
+        self.word_loads.forget(word);
+    }
+
+    /// Loads the whole entry tree of a word with one query per table and assembles it in memory.
+    #[allow(clippy::too_many_lines)]
+    async fn load_word_definitions(&self, word: &str) -> Result<Option<StoredWord>> {
         let mut transaction = self.pool.begin().await?;
 
+        let query = sqlx::query!(
+            r#"
+            select id, fetched_at
+            from words
+            where lower(word) = lower($1)
+            "#,
+            word
+        );
+
+        let Some(db_word) = query.fetch_optional(&mut *transaction).await? else {
+            return Ok(None);
+        };
+
         let query = sqlx::query_as!(
             DbWordEntry,
             r#"
-            select id as "id!", word as "word!"
+            select id as "id!", word_id, word as "word!", phonetic, origin, license_name, license_url
             from word_entries
-            where word = $1
+            where word_id = $1
+            order by id
             "#,
-            word
+            db_word.id
         );
 
         let db_word_entries = query.fetch_all(&mut *transaction).await?;
@@ -240,125 +514,197 @@ impl Repository {
             return Ok(None);
         }
 
-        let mut word_entries = Vec::new();
+        let word_entry_ids: Vec<i32> = db_word_entries.iter().map(|entry| entry.id).collect();
 
-        for db_word_entry in db_word_entries {
-            let mut word_entry = WordEntry {
-                word: word.to_owned(),
-                meanings: Vec::new(),
-                source_urls: Vec::new(),
-            };
-
-            let query = sqlx::query_as!(
-                DbSourceUrl,
-                r#"
-                select id as "id!", word_entry_id as "word_entry_id!", url as "url!"
-                from source_urls
-                where word_entry_id = $1
-                "#,
-                db_word_entry.id
-            );
+        let query = sqlx::query_as!(
+            DbSourceUrl,
+            r#"
+            select word_entry_id as "word_entry_id!", url as "url!"
+            from source_urls
+            where word_entry_id = any($1)
+            order by id
+            "#,
+            &word_entry_ids
+        );
 
-            let db_source_urls = query.fetch_all(&mut *transaction).await?;
+        let mut db_source_urls = group_by(query.fetch_all(&mut *transaction).await?, |url| {
+            url.word_entry_id
+        });
 
-            word_entry.source_urls.extend(
-                db_source_urls
-                    .into_iter()
-                    .map(|DbSourceUrl { url, .. }| url),
-            );
+        let query = sqlx::query_as!(
+            DbPhonetic,
+            r#"
+            select word_entry_id as "word_entry_id!", text, audio
+            from phonetics
+            where word_entry_id = any($1)
+            order by id
+            "#,
+            &word_entry_ids
+        );
 
-            let query = sqlx::query_as!(
-                DbMeaning,
-                r#"
-                select id as "id!", word_entry_id as "word_entry_id!", part_of_speech as "part_of_speech!"
-                from meanings
-                where word_entry_id = $1
-                "#,
-                db_word_entry.id
-            );
+        let mut db_phonetics = group_by(query.fetch_all(&mut *transaction).await?, |phonetic| {
+            phonetic.word_entry_id
+        });
 
-            let db_meanings = query.fetch_all(&mut *transaction).await?;
+        let query = sqlx::query_as!(
+            DbMeaning,
+            r#"
+            select id as "id!", word_entry_id as "word_entry_id!", part_of_speech as "part_of_speech!"
+            from meanings
+            where word_entry_id = any($1)
+            order by id
+            "#,
+            &word_entry_ids
+        );
 
-            for db_meaning in db_meanings {
-                let mut meaning = Meaning {
-                    part_of_speech: db_meaning.part_of_speech,
-                    definitions: Vec::new(),
-                    synonyms: Vec::new(),
-                    antonyms: Vec::new(),
-                };
+        let db_meanings = query.fetch_all(&mut *transaction).await?;
+        let meaning_ids: Vec<i32> = db_meanings.iter().map(|meaning| meaning.id).collect();
+        let mut db_meanings = group_by(db_meanings, |meaning| meaning.word_entry_id);
 
-                let query = sqlx::query_as!(
-                    DbDefinition,
-                    r#"
-                    select id as "id!", meaning_id as "meaning_id!", definition as "definition!", example
-                    from definitions
-                    where meaning_id = $1
-                    "#,
-                    db_meaning.id
-                );
-
-                let db_definitions = query.fetch_all(&mut *transaction).await?;
-                meaning.definitions.extend(db_definitions.into_iter().map(
-                    |DbDefinition {
-                         definition,
-                         example,
-                         ..
-                     }| {
-                        Definition {
-                            definition,
-                            example,
-                        }
-                    },
-                ));
-
-                let query = sqlx::query_as!(
-                    DbSynonym,
-                    r#"
-                    select id as "id!", meaning_id as "meaning_id!", synonym as "synonym!"
-                    from synonyms
-                    where meaning_id = $1
-                    "#,
-                    db_meaning.id
-                );
-
-                let db_synonyms = query.fetch_all(&mut *transaction).await?;
-                meaning.synonyms.extend(
-                    db_synonyms
-                        .into_iter()
-                        .map(|DbSynonym { synonym, .. }| synonym),
-                );
-
-                let query = sqlx::query_as!(
-                    DbAntonym,
-                    r#"
-                    select id as "id!", meaning_id as "meaning_id!", antonym as "antonym!"
-                    from antonyms
-                    where meaning_id = $1
-                    "#,
-                    db_meaning.id
-                );
-
-                let db_antonyms = query.fetch_all(&mut *transaction).await?;
-                meaning.antonyms.extend(
-                    db_antonyms
-                        .into_iter()
-                        .map(|DbAntonym { antonym, .. }| antonym),
-                );
+        let query = sqlx::query_as!(
+            DbDefinition,
+            r#"
+            select id as "id!", meaning_id as "meaning_id!", definition as "definition!", example
+            from definitions
+            where meaning_id = any($1)
+            order by id
+            "#,
+            &meaning_ids
+        );
 
-                word_entry.meanings.push(meaning);
-            }
-            word_entries.push(word_entry);
-        }
+        let db_definitions = query.fetch_all(&mut *transaction).await?;
+        let definition_ids: Vec<i32> = db_definitions
+            .iter()
+            .map(|definition| definition.id)
+            .collect();
+        let mut db_definitions = group_by(db_definitions, |definition| definition.meaning_id);
+
+        let query = sqlx::query_as!(
+            DbSynonym,
+            r#"
+            select meaning_id as "meaning_id!", synonym as "synonym!"
+            from synonyms
+            where meaning_id = any($1)
+            order by id
+            "#,
+            &meaning_ids
+        );
+
+        let mut db_synonyms = group_by(query.fetch_all(&mut *transaction).await?, |synonym| {
+            synonym.meaning_id
+        });
+
+        let query = sqlx::query_as!(
+            DbAntonym,
+            r#"
+            select meaning_id as "meaning_id!", antonym as "antonym!"
+            from antonyms
+            where meaning_id = any($1)
+            order by id
+            "#,
+            &meaning_ids
+        );
+
+        let mut db_antonyms = group_by(query.fetch_all(&mut *transaction).await?, |antonym| {
+            antonym.meaning_id
+        });
+
+        let query = sqlx::query_as!(
+            DbDefinitionSynonym,
+            r#"
+            select definition_id as "definition_id!", synonym as "synonym!"
+            from definition_synonyms
+            where definition_id = any($1)
+            order by id
+            "#,
+            &definition_ids
+        );
+
+        let mut db_definition_synonyms =
+            group_by(query.fetch_all(&mut *transaction).await?, |synonym| {
+                synonym.definition_id
+            });
+
+        let query = sqlx::query_as!(
+            DbDefinitionAntonym,
+            r#"
+            select definition_id as "definition_id!", antonym as "antonym!"
+            from definition_antonyms
+            where definition_id = any($1)
+            order by id
+            "#,
+            &definition_ids
+        );
+
+        let mut db_definition_antonyms =
+            group_by(query.fetch_all(&mut *transaction).await?, |antonym| {
+                antonym.definition_id
+            });
 
         transaction.commit().await?;
 
-        {
-            let mut cache_guard = self.words_cache.lock().unwrap();
+        let mut word_entries = Vec::new();
+
+        for db_word_entry in db_word_entries {
+            let mut meanings = Vec::new();
+
+            for db_meaning in take(&mut db_meanings, db_word_entry.id) {
+                let mut definitions = Vec::new();
+
+                for db_definition in take(&mut db_definitions, db_meaning.id) {
+                    definitions.push(Definition {
+                        definition: db_definition.definition,
+                        example: db_definition.example,
+                        synonyms: take(&mut db_definition_synonyms, db_definition.id)
+                            .into_iter()
+                            .map(|DbDefinitionSynonym { synonym, .. }| synonym)
+                            .collect(),
+                        antonyms: take(&mut db_definition_antonyms, db_definition.id)
+                            .into_iter()
+                            .map(|DbDefinitionAntonym { antonym, .. }| antonym)
+                            .collect(),
+                    });
+                }
 
-            cache_guard.put(word.to_owned(), word_entries.clone());
+                meanings.push(Meaning {
+                    part_of_speech: db_meaning.part_of_speech,
+                    definitions,
+                    synonyms: take(&mut db_synonyms, db_meaning.id)
+                        .into_iter()
+                        .map(|DbSynonym { synonym, .. }| synonym)
+                        .collect(),
+                    antonyms: take(&mut db_antonyms, db_meaning.id)
+                        .into_iter()
+                        .map(|DbAntonym { antonym, .. }| antonym)
+                        .collect(),
+                });
+            }
+
+            word_entries.push(WordEntry {
+                word: db_word_entry.word,
+                phonetic: db_word_entry.phonetic,
+                phonetics: take(&mut db_phonetics, db_word_entry.id)
+                    .into_iter()
+                    .map(|DbPhonetic { text, audio, .. }| Phonetic { text, audio })
+                    .collect(),
+                origin: db_word_entry.origin,
+                meanings,
+                license: db_word_entry
+                    .license_name
+                    .zip(db_word_entry.license_url)
+                    .map(|(name, url)| License { name, url }),
+                source_urls: take(&mut db_source_urls, db_word_entry.id)
+                    .into_iter()
+                    .map(|DbSourceUrl { url, .. }| url)
+                    .collect(),
+            });
         }
 
-        Ok(Some(word_entries))
+        Ok(Some(StoredWord {
+            word: word.to_owned(),
+            fetched_at: db_word.fetched_at,
+            entries: word_entries,
+        }))
     }
 
     pub(crate) async fn get_10_random_words(&self) -> Result<Vec<String>> {
@@ -382,22 +728,858 @@ impl Repository {
         Ok(words)
     }
 
-    pub(crate) async fn get_all_words(&self) -> Result<Vec<String>> {
-        let mut transaction = self.pool.begin().await?;
+    /// Stored words starting with `prefix` in alphabetical order.
+    pub(crate) async fn get_words_by_prefix(
+        &self,
+        prefix: &str,
+        limit: i64,
+    ) -> Result<Vec<String>> {
+        let pattern = format!("{}%", escape_like(prefix));
 
         let query = sqlx::query_as!(
             DbWord,
             r#"
             select word
             from words
-            "#
+            where word like $1
+            order by word
+            limit $2
+            "#,
+            pattern,
+            limit
         );
 
-        let words = query.fetch_all(&mut *transaction).await?;
+        let words = query.fetch_all(&self.pool).await?;
         let words = words.into_iter().map(|DbWord { word }| word).collect();
 
-        transaction.commit().await?;
+        Ok(words)
+    }
+
+    /// Stored words most similar to `word` by trigram similarity.
+    pub(crate) async fn get_similar_words(&self, word: &str, limit: i64) -> Result<Vec<String>> {
+        let query = sqlx::query_as!(
+            DbWord,
+            r#"
+            select word
+            from words
+            where word % $1
+            order by similarity(word, $1) desc, word
+            limit $2
+            "#,
+            word,
+            limit
+        );
+
+        let words = query.fetch_all(&self.pool).await?;
+        let words = words.into_iter().map(|DbWord { word }| word).collect();
+
+        Ok(words)
+    }
+
+    /// Ranks definitions and examples matching `query` using the `definitions.search` index.
+    pub(crate) async fn search_definitions(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<SearchHit>> {
+        let options = format!(
+            "StartSel={HIGHLIGHT_START}, StopSel={HIGHLIGHT_STOP}, MaxWords=35, MinWords=15"
+        );
+
+        let query = sqlx::query_as!(
+            DbSearchHit,
+            r#"
+            select
+                words.word,
+                word_entries.word as headword,
+                meanings.part_of_speech,
+                ts_headline('english', definitions.definition, search_query, $2) as "definition!",
+                ts_headline('english', definitions.example, search_query, $2) as example,
+                ts_rank(definitions.search, search_query) as "rank!"
+            from definitions
+            join meanings on meanings.id = definitions.meaning_id
+            join word_entries on word_entries.id = meanings.word_entry_id
+            join words on words.id = word_entries.word_id,
+            websearch_to_tsquery('english', $1) as search_query
+            where definitions.search @@ search_query
+            order by "rank!" desc, words.word
+            limit $3
+            "#,
+            query,
+            options,
+            limit
+        );
+
+        let hits = query
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|db_hit| SearchHit {
+                word: db_hit.word,
+                headword: db_hit.headword,
+                part_of_speech: db_hit.part_of_speech,
+                definition: split_highlights(&db_hit.definition),
+                example: db_hit.example.as_deref().map(split_highlights),
+                rank: db_hit.rank,
+            })
+            .collect();
+
+        Ok(hits)
+    }
+
+    /// Headwords whose definitions mention `query` or that list it as a synonym.
+    ///
+    /// Definition ranks of a word are summed, listing the term as a synonym adds
+    /// `SYNONYM_RANK` on top. The term itself is never returned.
+    pub(crate) async fn reverse_lookup(&self, query: &str, limit: i64) -> Result<Vec<ReverseHit>> {
+        let term = normalize_word(query);
+
+        let query = sqlx::query_as!(
+            DbReverseHit,
+            r#"
+            with definition_matches as (
+                select word_entries.word_id, definitions.definition, ts_rank('{0, 0, 0, 1}', definitions.search, search_query) as rank
+                from definitions
+                join meanings on meanings.id = definitions.meaning_id
+                join word_entries on word_entries.id = meanings.word_entry_id,
+                websearch_to_tsquery('english', $1) as search_query
+                where definitions.search @@ search_query
+            ),
+            definition_ranks as (
+                select word_id, sum(rank) as rank, (array_agg(definition order by rank desc))[1] as definition
+                from definition_matches
+                where rank > 0
+                group by word_id
+            ),
+            synonym_matches as (
+                select word_entries.word_id
+                from synonyms
+                join meanings on meanings.id = synonyms.meaning_id
+                join word_entries on word_entries.id = meanings.word_entry_id
+                where normalize(lower(btrim(synonyms.synonym)), NFC) = $2
+                union
+                select word_entries.word_id
+                from definition_synonyms
+                join definitions on definitions.id = definition_synonyms.definition_id
+                join meanings on meanings.id = definitions.meaning_id
+                join word_entries on word_entries.id = meanings.word_entry_id
+                where normalize(lower(btrim(definition_synonyms.synonym)), NFC) = $2
+            )
+            select
+                words.word,
+                definition_ranks.definition,
+                synonym_matches.word_id is not null as "synonym!",
+                (coalesce(definition_ranks.rank, 0)
+                    + case when synonym_matches.word_id is null then 0 else $3::real end)::real as "rank!"
+            from words
+            left join definition_ranks on definition_ranks.word_id = words.id
+            left join synonym_matches on synonym_matches.word_id = words.id
+            where (definition_ranks.word_id is not null or synonym_matches.word_id is not null)
+                and words.word <> $2
+            order by "rank!" desc, words.word
+            limit $4
+            "#,
+            query,
+            term,
+            SYNONYM_RANK,
+            limit
+        );
+
+        let hits = query
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|db_hit| ReverseHit {
+                word: db_hit.word,
+                definition: db_hit.definition,
+                synonym: db_hit.synonym,
+                rank: db_hit.rank,
+            })
+            .collect();
+
+        Ok(hits)
+    }
+
+    /// Counts stored words, optionally only those starting with `letter` and
+    /// having a meaning with `part_of_speech`.
+    pub(crate) async fn count_words(
+        &self,
+        letter: Option<char>,
+        part_of_speech: Option<&str>,
+    ) -> Result<i64> {
+        let pattern = letter.map(|letter| format!("{}%", escape_like(&letter.to_string())));
+
+        let total = sqlx::query!(
+            r#"
+            select count(*) as "total!"
+            from words
+            where ($1::text is null or word like $1)
+                and ($2::text is null or exists (
+                    select 1
+                    from word_entries
+                    join meanings on meanings.word_entry_id = word_entries.id
+                    where word_entries.word_id = words.id and meanings.part_of_speech = $2
+                ))
+            "#,
+            pattern,
+            part_of_speech
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .total;
+
+        Ok(total)
+    }
+
+    /// Words in alphabetical order that come after `after`.
+    pub(crate) async fn get_words_alphabetical(
+        &self,
+        letter: Option<char>,
+        part_of_speech: Option<&str>,
+        after: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<String>> {
+        let pattern = letter.map(|letter| format!("{}%", escape_like(&letter.to_string())));
+
+        let query = sqlx::query_as!(
+            DbWord,
+            r#"
+            select word
+            from words
+            where ($1::text is null or word like $1)
+                and ($2::text is null or word > $2)
+                and ($4::text is null or exists (
+                    select 1
+                    from word_entries
+                    join meanings on meanings.word_entry_id = word_entries.id
+                    where word_entries.word_id = words.id and meanings.part_of_speech = $4
+                ))
+            order by word
+            limit $3
+            "#,
+            pattern,
+            after,
+            limit,
+            part_of_speech
+        );
+
+        let words = query.fetch_all(&self.pool).await?;
+        let words = words.into_iter().map(|DbWord { word }| word).collect();
+
+        Ok(words)
+    }
+
+    /// Most recently added words that were added before the word with id `after`.
+    pub(crate) async fn get_words_recent(
+        &self,
+        letter: Option<char>,
+        part_of_speech: Option<&str>,
+        after: Option<i32>,
+        limit: i64,
+    ) -> Result<Vec<(i32, String)>> {
+        let pattern = letter.map(|letter| format!("{}%", escape_like(&letter.to_string())));
+
+        let query = sqlx::query!(
+            r#"
+            select id, word
+            from words
+            where ($1::text is null or word like $1)
+                and ($2::int is null or id < $2)
+                and ($4::text is null or exists (
+                    select 1
+                    from word_entries
+                    join meanings on meanings.word_entry_id = word_entries.id
+                    where word_entries.word_id = words.id and meanings.part_of_speech = $4
+                ))
+            order by id desc
+            limit $3
+            "#,
+            pattern,
+            after,
+            limit,
+            part_of_speech
+        );
+
+        let words = query
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|row| (row.id, row.word))
+            .collect();
 
         Ok(words)
     }
+
+    /// Which of the given words are stored.
+    pub(crate) async fn get_stored_words(&self, words: &[String]) -> Result<HashSet<String>> {
+        let stored_words = sqlx::query_as!(
+            DbWord,
+            r#"
+            select word
+            from words
+            where word = any($1)
+            "#,
+            words
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|DbWord { word }| word)
+        .collect();
+
+        Ok(stored_words)
+    }
+
+    /// Synonym and antonym edges reachable from a stored word in at most `depth` hops.
+    ///
+    /// Related words are followed only when they are stored themselves, each edge
+    /// is returned once with the hop it was first reached at.
+    pub(crate) async fn get_related_words(
+        &self,
+        word: &str,
+        depth: i32,
+    ) -> Result<Vec<RelatedEdge>> {
+        let edges = sqlx::query_as!(
+            DbRelatedEdge,
+            r#"
+            with recursive relations as not materialized (
+                select word_entries.word_id, synonyms.synonym as related, false as antonym
+                from synonyms
+                join meanings on meanings.id = synonyms.meaning_id
+                join word_entries on word_entries.id = meanings.word_entry_id
+                union all
+                select word_entries.word_id, antonyms.antonym, true
+                from antonyms
+                join meanings on meanings.id = antonyms.meaning_id
+                join word_entries on word_entries.id = meanings.word_entry_id
+                union all
+                select word_entries.word_id, definition_synonyms.synonym, false
+                from definition_synonyms
+                join definitions on definitions.id = definition_synonyms.definition_id
+                join meanings on meanings.id = definitions.meaning_id
+                join word_entries on word_entries.id = meanings.word_entry_id
+                union all
+                select word_entries.word_id, definition_antonyms.antonym, true
+                from definition_antonyms
+                join definitions on definitions.id = definition_antonyms.definition_id
+                join meanings on meanings.id = definitions.meaning_id
+                join word_entries on word_entries.id = meanings.word_entry_id
+            ),
+            graph (word, related, antonym, hops) as (
+                select words.word, normalize(lower(btrim(relations.related)), NFC), relations.antonym, 1
+                from words
+                join relations on relations.word_id = words.id
+                where words.word = $1
+                union
+                select words.word, normalize(lower(btrim(relations.related)), NFC), relations.antonym, graph.hops + 1
+                from graph
+                join words on words.word = graph.related
+                join relations on relations.word_id = words.id
+                where graph.hops < $2
+            )
+            select word as "word!", related as "related!", antonym as "antonym!", min(hops) as "hops!"
+            from graph
+            where word <> related
+            group by word, related, antonym
+            order by 4, 1, 2
+            "#,
+            word,
+            depth
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(
+            |DbRelatedEdge {
+                 word,
+                 related,
+                 antonym,
+                 hops,
+             }| RelatedEdge {
+                from: word,
+                to: related,
+                relation: if antonym {
+                    Relation::Antonym
+                } else {
+                    Relation::Synonym
+                },
+                hops,
+            },
+        )
+        .collect();
+
+        Ok(edges)
+    }
+
+    /// Number of words and meanings for every stored part of speech.
+    pub(crate) async fn get_parts_of_speech(&self) -> Result<Vec<PartOfSpeechCount>> {
+        let parts_of_speech = sqlx::query_as!(
+            DbPartOfSpeechCount,
+            r#"
+            select
+                meanings.part_of_speech,
+                count(distinct word_entries.word_id) as "words!",
+                count(*) as "meanings!"
+            from meanings
+            join word_entries on word_entries.id = meanings.word_entry_id
+            group by meanings.part_of_speech
+            order by 2 desc, meanings.part_of_speech
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(
+            |DbPartOfSpeechCount {
+                 part_of_speech,
+                 words,
+                 meanings,
+             }| PartOfSpeechCount {
+                part_of_speech,
+                words,
+                meanings,
+            },
+        )
+        .collect();
+
+        Ok(parts_of_speech)
+    }
+}
+
+/// Inserts entry trees with one `unnest` statement per table.
+///
+/// Ids of parent rows are reserved from their sequences up front, so child rows
+/// can reference them without relying on the order of `returning` results.
+#[allow(clippy::too_many_lines)]
+async fn insert_word_entries(
+    transaction: &mut Transaction<'_, Postgres>,
+    word_id: i32,
+    word_entries: &[WordEntry],
+) -> Result<()> {
+    let meanings = word_entries
+        .iter()
+        .flat_map(|word_entry| &word_entry.meanings);
+    let definitions_count = meanings
+        .clone()
+        .map(|meaning| meaning.definitions.len())
+        .sum();
+
+    let word_entry_ids = reserve_ids(transaction, "word_entries", word_entries.len()).await?;
+    let mut meaning_ids = reserve_ids(transaction, "meanings", meanings.count())
+        .await?
+        .into_iter();
+    let mut definition_ids = reserve_ids(transaction, "definitions", definitions_count)
+        .await?
+        .into_iter();
+
+    let mut db_word_entries = Vec::new();
+    let mut db_source_urls = Vec::new();
+    let mut db_phonetics = Vec::new();
+    let mut db_meanings = Vec::new();
+    let mut db_definitions = Vec::new();
+    let mut db_synonyms = Vec::new();
+    let mut db_antonyms = Vec::new();
+    let mut db_definition_synonyms = Vec::new();
+    let mut db_definition_antonyms = Vec::new();
+
+    for (word_entry, word_entry_id) in word_entries.iter().zip(word_entry_ids) {
+        db_word_entries.push(DbWordEntry {
+            id: word_entry_id,
+            word_id,
+            word: word_entry.word.clone(),
+            phonetic: word_entry.phonetic.clone(),
+            origin: word_entry.origin.clone(),
+            license_name: word_entry
+                .license
+                .as_ref()
+                .map(|license| license.name.clone()),
+            license_url: word_entry
+                .license
+                .as_ref()
+                .map(|license| license.url.clone()),
+        });
+
+        db_source_urls.extend(word_entry.source_urls.iter().map(|url| DbSourceUrl {
+            word_entry_id,
+            url: url.clone(),
+        }));
+
+        db_phonetics.extend(word_entry.phonetics.iter().map(|phonetic| DbPhonetic {
+            word_entry_id,
+            text: phonetic.text.clone(),
+            audio: phonetic.audio.clone(),
+        }));
+
+        for meaning in &word_entry.meanings {
+            let meaning_id = meaning_ids
+                .next()
+                .context("Not enough reserved meaning ids")?;
+
+            db_meanings.push(DbMeaning {
+                id: meaning_id,
+                word_entry_id,
+                part_of_speech: meaning.part_of_speech.clone(),
+            });
+
+            for definition in &meaning.definitions {
+                let definition_id = definition_ids
+                    .next()
+                    .context("Not enough reserved definition ids")?;
+
+                db_definitions.push(DbDefinition {
+                    id: definition_id,
+                    meaning_id,
+                    definition: definition.definition.clone(),
+                    example: definition.example.clone(),
+                });
+
+                db_definition_synonyms.extend(definition.synonyms.iter().map(|synonym| {
+                    DbDefinitionSynonym {
+                        definition_id,
+                        synonym: synonym.clone(),
+                    }
+                }));
+
+                db_definition_antonyms.extend(definition.antonyms.iter().map(|antonym| {
+                    DbDefinitionAntonym {
+                        definition_id,
+                        antonym: antonym.clone(),
+                    }
+                }));
+            }
+
+            db_synonyms.extend(meaning.synonyms.iter().map(|synonym| DbSynonym {
+                meaning_id,
+                synonym: synonym.clone(),
+            }));
+
+            db_antonyms.extend(meaning.antonyms.iter().map(|antonym| DbAntonym {
+                meaning_id,
+                antonym: antonym.clone(),
+            }));
+        }
+    }
+
+    sqlx::query!(
+        r#"
+        insert into word_entries (id, word_id, word, phonetic, origin, license_name, license_url)
+        select * from unnest(
+            $1::int[], $2::int[], $3::text[], $4::text[], $5::text[], $6::text[], $7::text[]
+        )
+        "#,
+        &column(&db_word_entries, |entry| entry.id),
+        &column(&db_word_entries, |entry| entry.word_id),
+        &column(&db_word_entries, |entry| entry.word.clone()),
+        &column(&db_word_entries, |entry| entry.phonetic.clone()) as &[Option<String>],
+        &column(&db_word_entries, |entry| entry.origin.clone()) as &[Option<String>],
+        &column(&db_word_entries, |entry| entry.license_name.clone()) as &[Option<String>],
+        &column(&db_word_entries, |entry| entry.license_url.clone()) as &[Option<String>]
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        insert into source_urls (word_entry_id, url)
+        select * from unnest($1::int[], $2::text[])
+        "#,
+        &column(&db_source_urls, |url| url.word_entry_id),
+        &column(&db_source_urls, |url| url.url.clone())
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        insert into phonetics (word_entry_id, text, audio)
+        select * from unnest($1::int[], $2::text[], $3::text[])
+        "#,
+        &column(&db_phonetics, |phonetic| phonetic.word_entry_id),
+        &column(&db_phonetics, |phonetic| phonetic.text.clone()) as &[Option<String>],
+        &column(&db_phonetics, |phonetic| phonetic.audio.clone()) as &[Option<String>]
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        insert into meanings (id, word_entry_id, part_of_speech)
+        select * from unnest($1::int[], $2::int[], $3::text[])
+        "#,
+        &column(&db_meanings, |meaning| meaning.id),
+        &column(&db_meanings, |meaning| meaning.word_entry_id),
+        &column(&db_meanings, |meaning| meaning.part_of_speech.clone())
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        insert into definitions (id, meaning_id, definition, example)
+        select * from unnest($1::int[], $2::int[], $3::text[], $4::text[])
+        "#,
+        &column(&db_definitions, |definition| definition.id),
+        &column(&db_definitions, |definition| definition.meaning_id),
+        &column(&db_definitions, |definition| definition.definition.clone()),
+        &column(&db_definitions, |definition| definition.example.clone()) as &[Option<String>]
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        insert into synonyms (meaning_id, synonym)
+        select * from unnest($1::int[], $2::text[])
+        "#,
+        &column(&db_synonyms, |synonym| synonym.meaning_id),
+        &column(&db_synonyms, |synonym| synonym.synonym.clone())
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        insert into antonyms (meaning_id, antonym)
+        select * from unnest($1::int[], $2::text[])
+        "#,
+        &column(&db_antonyms, |antonym| antonym.meaning_id),
+        &column(&db_antonyms, |antonym| antonym.antonym.clone())
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        insert into definition_synonyms (definition_id, synonym)
+        select * from unnest($1::int[], $2::text[])
+        "#,
+        &column(&db_definition_synonyms, |synonym| synonym.definition_id),
+        &column(&db_definition_synonyms, |synonym| synonym.synonym.clone())
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        insert into definition_antonyms (definition_id, antonym)
+        select * from unnest($1::int[], $2::text[])
+        "#,
+        &column(&db_definition_antonyms, |antonym| antonym.definition_id),
+        &column(&db_definition_antonyms, |antonym| antonym.antonym.clone())
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+/// Reserves `count` values from the `id` sequence of `table`.
+async fn reserve_ids(
+    transaction: &mut Transaction<'_, Postgres>,
+    table: &str,
+    count: usize,
+) -> Result<Vec<i32>> {
+    let count = i32::try_from(count)?;
+
+    let ids = sqlx::query!(
+        r#"
+        select nextval(pg_get_serial_sequence($1, 'id'))::int as "id!"
+        from generate_series(1, $2)
+        "#,
+        table,
+        count
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| row.id)
+    .collect();
+
+    Ok(ids)
+}
+
+/// Escapes `LIKE` wildcards, so `text` only matches literally.
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for character in text.chars() {
+        if matches!(character, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(character);
+    }
+
+    escaped
+}
+
+/// Splits `ts_headline` output into highlighted and plain parts.
+fn split_highlights(text: &str) -> Vec<Highlight> {
+    let mut highlights = Vec::new();
+
+    for (index, part) in text.split(HIGHLIGHT_START).enumerate() {
+        let (highlighted, plain) = match part.split_once(HIGHLIGHT_STOP) {
+            Some((highlighted, plain)) if index > 0 => (highlighted, plain),
+            _ => ("", part),
+        };
+
+        for (text, highlighted) in [(highlighted, true), (plain, false)] {
+            if !text.is_empty() {
+                highlights.push(Highlight {
+                    text: text.to_owned(),
+                    highlighted,
+                });
+            }
+        }
+    }
+
+    highlights
+}
+
+fn column<T, U>(rows: &[T], field: impl Fn(&T) -> U) -> Vec<U> {
+    rows.iter().map(field).collect()
+}
+
+fn group_by<T>(rows: Vec<T>, key: impl Fn(&T) -> i32) -> HashMap<i32, Vec<T>> {
+    let mut groups: HashMap<i32, Vec<T>> = HashMap::new();
+
+    for row in rows {
+        groups.entry(key(&row)).or_default().push(row);
+    }
+
+    groups
+}
+
+fn take<T>(groups: &mut HashMap<i32, Vec<T>>, id: i32) -> Vec<T> {
+    groups.remove(&id).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Instant,
+    };
+
+    use super::*;
+    use crate::provider::FilesProvider;
+
+    /// Counts the statements sqlx logs, every statement is one round trip.
+    struct StatementCounter;
+
+    static STATEMENTS: AtomicUsize = AtomicUsize::new(0);
+
+    impl log::Log for StatementCounter {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target() == "sqlx::query"
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                STATEMENTS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn word_entries(entries: usize, meanings: usize, definitions: usize) -> Vec<WordEntry> {
+        (0..entries)
+            .map(|entry| WordEntry {
+                word: "benchmark".to_owned(),
+                phonetic: Some(format!("/bench{entry}/")),
+                phonetics: vec![Phonetic {
+                    text: Some(format!("/bench{entry}/")),
+                    audio: None,
+                }],
+                origin: None,
+                meanings: (0..meanings)
+                    .map(|meaning| Meaning {
+                        part_of_speech: format!("part{meaning}"),
+                        definitions: (0..definitions)
+                            .map(|definition| Definition {
+                                definition: format!("Definition {entry}.{meaning}.{definition}."),
+                                example: Some("An example.".to_owned()),
+                                synonyms: vec![format!("synonym{definition}")],
+                                antonyms: vec![format!("antonym{definition}")],
+                            })
+                            .collect(),
+                        synonyms: vec![format!("synonym{meaning}")],
+                        antonyms: vec![format!("antonym{meaning}")],
+                    })
+                    .collect(),
+                license: None,
+                source_urls: vec!["https://example.com".to_owned()],
+            })
+            .collect()
+    }
+
+    /// Statements the per-entry loader replaced in a8c1cc3 sent for a word: `BEGIN`,
+    /// the entries, per entry its source URLs, phonetics and meanings, per meaning
+    /// its definitions, synonyms and antonyms, per definition its synonyms and
+    /// antonyms, and `COMMIT`.
+    fn per_entry_statements(entries: usize, meanings: usize, definitions: usize) -> usize {
+        let meanings = entries * meanings;
+
+        2 + 1 + 3 * entries + 3 * meanings + 2 * meanings * definitions
+    }
+
+    /// Compares cold loads of words of growing size with the per-entry loader.
+    ///
+    /// Runs migrations and writes to the database, point it at a scratch one:
+    /// `BENCH_DATABASE_URL=postgres://... cargo test --release -- --ignored --nocapture cold_load`.
+    #[tokio::test]
+    #[ignore = "needs a scratch database in BENCH_DATABASE_URL"]
+    async fn cold_load_statements_and_latency() -> Result<()> {
+        log::set_logger(&StatementCounter).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let database_url = Url::parse(
+            &std::env::var("BENCH_DATABASE_URL").context("BENCH_DATABASE_URL is not set")?,
+        )?;
+        let provider = Arc::new(FilesProvider::new("benchmark".into()));
+        let repository =
+            Repository::initialize(database_url, provider, Duration::from_secs(60)).await?;
+
+        let mut counts = Vec::new();
+        for (word, size) in [
+            ("benchmark-small", (1, 1, 1)),
+            ("benchmark-medium", (4, 5, 6)),
+            ("benchmark-large", (20, 10, 10)),
+        ] {
+            let (entries, meanings, definitions) = size;
+            repository.delete_word(word).await?;
+            repository
+                .add_word_entries(word, word_entries(entries, meanings, definitions))
+                .await?;
+
+            let mut latencies = Vec::new();
+            let mut statements = 0;
+            for _ in 0..20 {
+                STATEMENTS.store(0, Ordering::Relaxed);
+                let started = Instant::now();
+
+                let stored_word = repository.load_word_definitions(word).await?;
+
+                latencies.push(started.elapsed());
+                statements = STATEMENTS.load(Ordering::Relaxed);
+                assert!(stored_word.is_some());
+            }
+            latencies.sort();
+
+            let per_entry = per_entry_statements(entries, meanings, definitions);
+            println!(
+                "{word} ({entries}x{meanings}x{definitions}): {statements} statements, \
+                 per-entry loader {per_entry}, median cold load {:?}",
+                latencies[latencies.len() / 2]
+            );
+            assert!(statements <= per_entry);
+            counts.push(statements);
+
+            repository.delete_word(word).await?;
+        }
+
+        assert!(counts.iter().all(|&count| count == counts[0]));
+
+        Ok(())
+    }
 }
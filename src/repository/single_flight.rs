@@ -0,0 +1,146 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use tokio::sync::OnceCell;
+
+type Call<T> = Arc<OnceCell<Result<T, Arc<anyhow::Error>>>>;
+
+/// Deduplicates concurrent calls with the same key, every caller gets the
+/// result of the one call that actually runs.
+///
+/// If that caller is cancelled, one of the waiting callers runs its own call instead.
+pub(crate) struct SingleFlight<T> {
+    calls: Mutex<HashMap<String, Call<T>>>,
+}
+
+impl<T: Clone> SingleFlight<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) async fn run<F, Fut>(&self, key: &str, call: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let cell = {
+            let mut calls = self.calls.lock().unwrap();
+
+            calls.entry(key.to_owned()).or_default().clone()
+        };
+
+        let result = cell
+            .get_or_init(|| async { call().await.map_err(Arc::new) })
+            .await
+            .clone();
+
+        {
+            let mut calls = self.calls.lock().unwrap();
+
+            // Later callers start a new call, unless one has already replaced this one.
+            if calls
+                .get(key)
+                .is_some_and(|current| Arc::ptr_eq(current, &cell))
+            {
+                calls.remove(key);
+            }
+        }
+
+        result.map_err(|err| SharedError(err).into())
+    }
+
+    /// Makes later callers with the key start a new call instead of joining the running one.
+    pub(crate) fn forget(&self, key: &str) {
+        self.calls.lock().unwrap().remove(key);
+    }
+}
+
+/// Error of a call shared by several callers, its source chain is the original one.
+struct SharedError(Arc<anyhow::Error>);
+
+impl fmt::Debug for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl std::error::Error for SharedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.as_ref().as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use tokio::task::JoinSet;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_calls_run_once() {
+        let single_flight = Arc::new(SingleFlight::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let mut tasks = JoinSet::new();
+        for _ in 0..10 {
+            let single_flight = Arc::clone(&single_flight);
+            let calls = Arc::clone(&calls);
+
+            tasks.spawn(async move {
+                single_flight
+                    .run("word", || async {
+                        calls.fetch_add(1, Ordering::Relaxed);
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        Ok(42)
+                    })
+                    .await
+            });
+        }
+
+        while let Some(result) = tasks.join_next().await {
+            assert_eq!(result.unwrap().unwrap(), 42);
+        }
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // A finished call is not reused.
+        let result = single_flight.run("word", || async { Ok(43) }).await;
+        assert_eq!(result.unwrap(), 43);
+        assert!(single_flight.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancelled_leader_hands_over_to_waiter() {
+        let single_flight = SingleFlight::new();
+
+        let mut leader = Box::pin(single_flight.run("word", || async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(1)
+        }));
+        let mut waiter = Box::pin(single_flight.run("word", || async { Ok(2) }));
+
+        let timeout = Duration::from_millis(1);
+        assert!(tokio::time::timeout(timeout, &mut leader).await.is_err());
+        assert!(tokio::time::timeout(timeout, &mut waiter).await.is_err());
+        drop(leader);
+
+        assert_eq!(waiter.await.unwrap(), 2);
+    }
+}